@@ -1,9 +1,4 @@
-/* Use with PostgreSQL */
-
-\c postgres;
-DROP DATABASE IF EXISTS nutrinow;
-CREATE DATABASE nutrinow;
-\c nutrinow;
+/* Baseline schema (the former 'setup/base.sql'), with the nutrient and default nutrition seed data */
 
 CREATE TABLE user_account (
     id SERIAL,
@@ -0,0 +1,2 @@
+/* Encoded Argon2id hashes are longer than SHA-256 hashes. Legacy SHA-256 hashes keep working and are rehashed on the user's next login. */
+ALTER TABLE user_account ALTER COLUMN password_hash TYPE VARCHAR(255);
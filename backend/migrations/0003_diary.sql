@@ -0,0 +1,20 @@
+/* Food diary, with what was actually eaten on each date */
+CREATE TABLE IF NOT EXISTS diary_entry (
+    id SERIAL,
+    user_id INTEGER NOT NULL,
+    entry_date DATE NOT NULL,
+    meal_name VARCHAR(100) NULL, /* optional meal slot (e.g 'Breakfast') */
+    serving_id INTEGER NOT NULL,
+    amount FLOAT NOT NULL,
+    PRIMARY KEY(id),
+    FOREIGN KEY (user_id) REFERENCES user_account(id),
+    FOREIGN KEY (serving_id) REFERENCES serving(id)
+);
+
+CREATE INDEX IF NOT EXISTS diary_entry_user_date ON diary_entry(user_id, entry_date);
+
+/* The former 'setup/upgrade_diary.sql' declared the reference columns as SERIAL */
+ALTER TABLE diary_entry ALTER COLUMN user_id DROP DEFAULT;
+ALTER TABLE diary_entry ALTER COLUMN serving_id DROP DEFAULT;
+DROP SEQUENCE IF EXISTS diary_entry_user_id_seq;
+DROP SEQUENCE IF EXISTS diary_entry_serving_id_seq;
@@ -0,0 +1,6 @@
+/* Sessions expire at a precise time and are renewed on every request (expired sessions are rejected by the server) */
+ALTER TABLE user_session ALTER COLUMN expiry_date TYPE TIMESTAMPTZ;
+ALTER TABLE user_session ADD COLUMN IF NOT EXISTS public_id UUID UNIQUE NOT NULL DEFAULT gen_random_uuid(); /* identifies the session without exposing the session token */
+ALTER TABLE user_session ADD COLUMN IF NOT EXISTS user_agent TEXT NULL;
+ALTER TABLE user_session ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE user_session ADD COLUMN IF NOT EXISTS last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,18 @@
+/* Body weight history. The weight of the user account follows the most recent entry. */
+CREATE TABLE IF NOT EXISTS weight_entry (
+    id SERIAL,
+    user_id INTEGER NOT NULL,
+    entry_date DATE NOT NULL,
+    weight FLOAT NOT NULL, /* in kilograms */
+    PRIMARY KEY(id),
+    FOREIGN KEY (user_id) REFERENCES user_account(id),
+    UNIQUE(user_id, entry_date)
+);
+
+/* The former 'setup/upgrade_weight_history.sql' declared the reference column as SERIAL */
+ALTER TABLE weight_entry ALTER COLUMN user_id DROP DEFAULT;
+DROP SEQUENCE IF EXISTS weight_entry_user_id_seq;
+
+/* Start the weight history of existing users with their current weight */
+INSERT INTO weight_entry(user_id, entry_date, weight) SELECT id, CURRENT_DATE, weight FROM user_account
+ON CONFLICT (user_id, entry_date) DO NOTHING;
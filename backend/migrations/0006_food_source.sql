@@ -0,0 +1,2 @@
+/* FoodData Central ID of the foods imported from USDA, so that re-imports update them instead of duplicating them */
+ALTER TABLE food ADD COLUMN fdc_id INTEGER NULL UNIQUE;
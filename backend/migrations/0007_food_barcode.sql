@@ -0,0 +1,9 @@
+/* Barcodes of the foods, normalized to 14 digits (GTIN-14, which covers UPC-A, EAN-13 and EAN-8) */
+CREATE TABLE food_barcode (
+    food_id INTEGER NOT NULL,
+    code CHAR(14) NOT NULL,
+    PRIMARY KEY(food_id, code),
+    FOREIGN KEY (food_id) REFERENCES food(id)
+);
+
+CREATE INDEX food_barcode_code ON food_barcode(code);
@@ -0,0 +1,6 @@
+/* Full-text and fuzzy (trigram) search on food names */
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+/* NOTE: The search queries must use the same expression to make use of this index */
+CREATE INDEX food_name_fts ON food USING GIN (to_tsvector('english', name));
+CREATE INDEX food_name_trgm ON food USING GIN (name gin_trgm_ops);
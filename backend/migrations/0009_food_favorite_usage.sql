@@ -0,0 +1,22 @@
+/* Foods starred by the users */
+CREATE TABLE food_favorite (
+    user_id INTEGER NOT NULL,
+    food_id INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY(user_id, food_id),
+    FOREIGN KEY (user_id) REFERENCES user_account(id),
+    FOREIGN KEY (food_id) REFERENCES food(id)
+);
+
+/* Servings used by the users in their meals, for the recently used foods */
+CREATE TABLE serving_usage (
+    user_id INTEGER NOT NULL,
+    serving_id INTEGER NOT NULL,
+    use_count INTEGER NOT NULL DEFAULT 1,
+    last_used TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY(user_id, serving_id),
+    FOREIGN KEY (user_id) REFERENCES user_account(id),
+    FOREIGN KEY (serving_id) REFERENCES serving(id)
+);
+
+CREATE INDEX serving_usage_user_last_used ON serving_usage(user_id, last_used);
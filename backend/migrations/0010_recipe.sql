@@ -0,0 +1,22 @@
+/* Recipes are foods made of other foods' servings. The yield serving holds the total
+   weight or number of portions, and the nutrients calculated from the ingredients. */
+CREATE TABLE recipe (
+    food_id INTEGER NOT NULL,
+    serving_id INTEGER UNIQUE NOT NULL, /* yield serving (e.g '450 g' or '4 portion') */
+    PRIMARY KEY(food_id),
+    FOREIGN KEY (food_id) REFERENCES food(id),
+    FOREIGN KEY (serving_id) REFERENCES serving(id)
+);
+
+CREATE TABLE recipe_ingredient (
+    id SERIAL,
+    recipe_id INTEGER NOT NULL, /* food ID of the recipe */
+    serving_id INTEGER NOT NULL,
+    amount FLOAT NOT NULL, /* in units of the serving, like 'meal_serving.amount' */
+    PRIMARY KEY(id),
+    FOREIGN KEY (recipe_id) REFERENCES recipe(food_id),
+    FOREIGN KEY (serving_id) REFERENCES serving(id)
+);
+
+CREATE INDEX recipe_ingredient_recipe ON recipe_ingredient(recipe_id);
+CREATE INDEX recipe_ingredient_serving ON recipe_ingredient(serving_id);
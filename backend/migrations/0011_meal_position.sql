@@ -0,0 +1,15 @@
+/* Explicit order of the meals in a diet, and of the servings in a meal */
+ALTER TABLE meal ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE meal_serving ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+/* Keep the current (creation) order */
+UPDATE meal SET position = ordered.position
+FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY diet_id ORDER BY id) - 1 AS position FROM meal) AS ordered
+WHERE meal.id = ordered.id;
+
+UPDATE meal_serving SET position = ordered.position
+FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY meal_id ORDER BY id) - 1 AS position FROM meal_serving) AS ordered
+WHERE meal_serving.id = ordered.id;
+
+CREATE INDEX meal_diet_position ON meal(diet_id, position);
+CREATE INDEX meal_serving_meal_position ON meal_serving(meal_id, position);
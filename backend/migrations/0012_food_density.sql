@@ -0,0 +1,2 @@
+/* Density of a food in g/ml, when it's known, to convert between mass and volume units */
+ALTER TABLE food ADD COLUMN density FLOAT NULL CHECK (density > 0);
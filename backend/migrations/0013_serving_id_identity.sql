@@ -0,0 +1,3 @@
+/* Generate the serving IDs in the database, instead of calculating 'MAX(id) + 1' (which fails with concurrent inserts) */
+ALTER TABLE serving ALTER COLUMN id ADD GENERATED BY DEFAULT AS IDENTITY;
+SELECT setval(pg_get_serial_sequence('serving', 'id'), COALESCE(MAX(id), 0) + 1, false) FROM serving;
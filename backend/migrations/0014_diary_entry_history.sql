@@ -0,0 +1,15 @@
+/* Diary entries keep a copy of their food when it is deleted, so that the history of what was eaten stays complete.
+   Entries without a serving use the copy: the food name, the serving unit and base, and the nutrients for that base. */
+ALTER TABLE diary_entry ALTER COLUMN serving_id DROP NOT NULL;
+ALTER TABLE diary_entry ADD COLUMN food_name VARCHAR(255) NULL;
+ALTER TABLE diary_entry ADD COLUMN serving_unit VARCHAR(100) NULL;
+ALTER TABLE diary_entry ADD COLUMN serving_base FLOAT NULL;
+
+CREATE TABLE diary_entry_nutrient (
+    diary_entry_id INTEGER NOT NULL,
+    nutrient_id INTEGER NOT NULL,
+    amount FLOAT NOT NULL, /* for the serving base of the entry */
+    PRIMARY KEY(diary_entry_id, nutrient_id),
+    FOREIGN KEY (diary_entry_id) REFERENCES diary_entry(id) ON DELETE CASCADE,
+    FOREIGN KEY (nutrient_id) REFERENCES nutrient(id)
+);
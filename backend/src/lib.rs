@@ -5,20 +5,63 @@ pub mod utils;
 
 use actix_files::NamedFile;
 use actix_web::{
-    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse},
     get, web, App, HttpRequest, Result,
 };
 use settings::Settings;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use utils::request::refresh_session_cookies;
+use sqlx::{
+    migrate::{Migrate, MigrateDatabase, MigrateError, Migrator},
+    postgres::PgPoolOptions,
+    Pool, Postgres,
+};
 use std::path::{Path, PathBuf};
 
 pub async fn connect_db(settings: &Settings) -> Result<Pool<Postgres>, sqlx::Error> {
+    // Stand up the database on the first run, the migrations create the schema
+    if !Postgres::database_exists(&settings.database_url).await? {
+        Postgres::create_database(&settings.database_url).await?;
+    }
+
     PgPoolOptions::new()
         .max_connections(10)
         .connect(&settings.database_url)
         .await
 }
 
+/// Applies the pending migrations from 'migrations/' (embedded in the binary)
+pub async fn migrate_db(dbpool: &Pool<Postgres>) -> Result<(), MigrateError> {
+    let migrator = sqlx::migrate!();
+    adopt_baseline(&migrator, dbpool).await?;
+    migrator.run(dbpool).await
+}
+
+/// Databases created with the former 'setup/base.sql' already have the baseline schema, but no record of
+/// the migrations. The baseline is recorded as applied, and the later migrations upgrade them as usual.
+async fn adopt_baseline(migrator: &Migrator, dbpool: &Pool<Postgres>) -> Result<(), MigrateError> {
+    let (has_schema, has_migrations): (bool, bool) = sqlx::query_as(
+        "SELECT to_regclass('user_account') IS NOT NULL, to_regclass('_sqlx_migrations') IS NOT NULL",
+    )
+    .fetch_one(dbpool)
+    .await?;
+
+    if !has_schema || has_migrations {
+        return Ok(());
+    }
+
+    let baseline = &migrator.migrations[0];
+    let mut conn = dbpool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES ($1, $2, TRUE, $3, 0)")
+        .bind(baseline.version)
+        .bind(&*baseline.description)
+        .bind(&*baseline.checksum)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
 #[get("/")]
 async fn root() -> Result<NamedFile> {
     let file_path = Path::new("static").join("index.html");
@@ -37,6 +80,7 @@ async fn files(req: HttpRequest) -> Result<NamedFile> {
 
 pub fn create_app(
     dbpool: Pool<Postgres>,
+    settings: Settings,
 ) -> App<
     impl ServiceFactory<
         ServiceRequest,
@@ -47,7 +91,16 @@ pub fn create_app(
     >,
 > {
     App::new()
+        .wrap_fn(|req, srv| {
+            let fut = srv.call(req);
+            async move {
+                let mut res = fut.await?;
+                refresh_session_cookies(&mut res);
+                Ok(res)
+            }
+        })
         .app_data(web::Data::new(dbpool))
+        .app_data(web::Data::new(settings))
         .service(routes::api_register)
         .service(routes::api_login)
         .service(routes::api_logout)
@@ -62,11 +115,60 @@ pub fn create_app(
         .service(routes::api_add_meal)
         .service(routes::api_delete_meal)
         .service(routes::api_food_search)
+        .service(routes::api_food_browse)
         .service(routes::api_add_meal_serving)
         .service(routes::api_delete_meal_serving)
         .service(routes::api_edit_meal_serving)
         .service(routes::api_food)
         .service(routes::api_duplicate_diet)
+        .service(routes::api_add_diet_nutrient)
+        .service(routes::api_edit_diet_nutrient)
+        .service(routes::api_delete_diet_nutrient)
+        .service(routes::api_new_food)
+        .service(routes::api_edit_food)
+        .service(routes::api_delete_food)
+        .service(routes::api_add_food_serving)
+        .service(routes::api_edit_food_serving)
+        .service(routes::api_delete_food_serving)
+        .service(routes::api_edit_serving_nutrient)
+        .service(routes::api_delete_serving_nutrient)
+        .service(routes::api_diary)
+        .service(routes::api_add_diary_entry)
+        .service(routes::api_edit_diary_entry)
+        .service(routes::api_delete_diary_entry)
+        .service(routes::api_copy_diet_to_diary)
+        .service(routes::api_diet_totals)
+        .service(routes::api_sessions)
+        .service(routes::api_revoke_session)
+        .service(routes::api_logout_all)
+        .service(routes::api_edit_user)
+        .service(routes::api_change_password)
+        .service(routes::api_weight_log)
+        .service(routes::api_add_weight_entry)
+        .service(routes::api_delete_weight_entry)
+        .service(routes::api_weight_trend)
+        .service(routes::api_delete_user)
+        .service(routes::api_export_user)
+        .service(routes::api_food_barcode)
+        .service(routes::api_add_food_barcode)
+        .service(routes::api_delete_food_barcode)
+        .service(routes::api_favorite_foods)
+        .service(routes::api_add_favorite_food)
+        .service(routes::api_delete_favorite_food)
+        .service(routes::api_recent_foods)
+        .service(routes::api_recipe)
+        .service(routes::api_new_recipe)
+        .service(routes::api_edit_recipe_yield)
+        .service(routes::api_add_recipe_ingredient)
+        .service(routes::api_edit_recipe_ingredient)
+        .service(routes::api_delete_recipe_ingredient)
+        .service(routes::api_reorder_meals)
+        .service(routes::api_reorder_meal_servings)
+        .service(routes::api_move_meal_serving)
+        .service(routes::api_edit_meal)
+        .service(routes::api_copy_meal)
+        .service(routes::api_move_meal)
+        .service(routes::api_parse_nutrition_label)
         /* Serve index.html on / and on any unmatched routes (necessary to work with Vue.js) */
         .service(root)
         .service(files)
@@ -1,8 +1,11 @@
 use actix_web::{middleware::Logger, HttpServer};
-use nutrinow::{connect_db, create_app, settings::Settings};
+use nutrinow::{connect_db, create_app, migrate_db, settings::Settings, utils::usda::import_usda};
+use std::path::PathBuf;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    env_logger::init();
+
     let settings = Settings::load()
         .expect("Failed to load settings. Check your 'Config.toml' or 'ConfigDebug.toml'.");
 
@@ -10,10 +13,34 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to connect to database.");
 
-    env_logger::init();
+    migrate_db(&dbpool)
+        .await
+        .expect("Failed to apply database migrations.");
+
+    let args : Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        // 'nutrinow migrate' only brings the database up to date
+        Some("migrate") => return Ok(()),
+        // 'nutrinow import-usda <files or directories>' imports FoodData Central JSON files
+        Some("import-usda") => {
+            let paths : Vec<PathBuf> = args[2..].iter().map(PathBuf::from).collect();
+            if paths.is_empty() {
+                eprintln!("Usage: nutrinow import-usda <files or directories>");
+                std::process::exit(1);
+            }
+
+            let summary = import_usda(&paths, &dbpool)
+                .await
+                .expect("Failed to import USDA foods.");
+            println!("{}", summary);
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    HttpServer::new(move || create_app(dbpool.clone()).wrap(Logger::default()))
-        .bind((settings.host, settings.port))?
+    let address = (settings.host.clone(), settings.port);
+    HttpServer::new(move || create_app(dbpool.clone(), settings.clone()).wrap(Logger::default()))
+        .bind(address)?
         .run()
         .await
 }
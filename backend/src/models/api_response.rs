@@ -1,18 +1,39 @@
+use std::fmt;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use serde::Serialize;
 
-#[derive(Serialize)]
+/// Problem with one field of a request (e.g 'weight': 'must be positive')
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field : String,
+    pub message : String
+}
+
+impl FieldError {
+    pub fn new(field : &str, message : &str) -> Self {
+        Self { field: field.to_string(), message: message.to_string() }
+    }
+}
+
+#[derive(Serialize, Debug)]
 pub struct ApiResponseError {
-    err : String
+    err : String,
+    code : &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields : Vec<FieldError>
 }
 
+#[derive(Debug)]
 pub enum ApiError {
     RegistrationFailed,
     InvalidInput,
+    InvalidFields(Vec<FieldError>),
     AuthFailed,
     NotLoggedIn,
     QueryDiets,
     QueryDietNutrition,
     AccessDenied,
+    NotFound,
     QueryNutrients,
     QueryMeals,
     CreateDiet,
@@ -25,36 +46,270 @@ pub enum ApiError {
     DeleteMealServing,
     EditMealServing,
     QueryFood,
-    DuplicateDiet
+    DuplicateDiet,
+    AddDietNutrient,
+    DietNutrientExists,
+    EditDietNutrient,
+    DeleteDietNutrient,
+    CreateFood,
+    EditFood,
+    DeleteFood,
+    AddFoodServing,
+    InvalidRelativeServing,
+    EditFoodServing,
+    DeleteFoodServing,
+    EditServingNutrient,
+    DeleteServingNutrient,
+    QueryDiary,
+    AddDiaryEntry,
+    EditDiaryEntry,
+    DeleteDiaryEntry,
+    CopyDietToDiary,
+    QueryDietTotals,
+    QuerySessions,
+    RevokeSession,
+    QueryUser,
+    EditUser,
+    EmailInUse,
+    ChangePassword,
+    QueryWeightLog,
+    AddWeightEntry,
+    DeleteWeightEntry,
+    DeleteUser,
+    DeleteCatalogUser,
+    ExportUser,
+    InvalidBarcode,
+    BarcodeNotFound,
+    AddFoodBarcode,
+    DeleteFoodBarcode,
+    QueryFavoriteFoods,
+    AddFavoriteFood,
+    DeleteFavoriteFood,
+    QueryRecentFoods,
+    CreateRecipe,
+    QueryRecipe,
+    EditRecipe,
+    NotARecipe,
+    AddRecipeIngredient,
+    EditRecipeIngredient,
+    DeleteRecipeIngredient,
+    RecipeCycle,
+    RecipeServing,
+    ReorderMeals,
+    ReorderMealServings,
+    MoveMealServing,
+    EditMeal,
+    CopyMeal,
+    MoveMeal,
+    ParseNutritionLabel,
+    UnitMismatch(String)
+}
+
+impl ApiError {
+    /// Machine-readable code of the error, which doesn't change with the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::RegistrationFailed => "registration_failed",
+            ApiError::InvalidInput => "invalid_input",
+            ApiError::InvalidFields(_) => "invalid_input",
+            ApiError::AuthFailed => "auth_failed",
+            ApiError::NotLoggedIn => "not_logged_in",
+            ApiError::QueryDiets => "query_diets",
+            ApiError::QueryDietNutrition => "query_diet_nutrition",
+            ApiError::AccessDenied => "access_denied",
+            ApiError::NotFound => "not_found",
+            ApiError::QueryNutrients => "query_nutrients",
+            ApiError::QueryMeals => "query_meals",
+            ApiError::CreateDiet => "create_diet",
+            ApiError::EditDiet => "edit_diet",
+            ApiError::DeleteDiet => "delete_diet",
+            ApiError::CreateMeal => "create_meal",
+            ApiError::DeleteMeal => "delete_meal",
+            ApiError::SearchFoods => "search_foods",
+            ApiError::AddMealServing => "add_meal_serving",
+            ApiError::DeleteMealServing => "delete_meal_serving",
+            ApiError::EditMealServing => "edit_meal_serving",
+            ApiError::QueryFood => "query_food",
+            ApiError::DuplicateDiet => "duplicate_diet",
+            ApiError::AddDietNutrient => "add_diet_nutrient",
+            ApiError::DietNutrientExists => "diet_nutrient_exists",
+            ApiError::EditDietNutrient => "edit_diet_nutrient",
+            ApiError::DeleteDietNutrient => "delete_diet_nutrient",
+            ApiError::CreateFood => "create_food",
+            ApiError::EditFood => "edit_food",
+            ApiError::DeleteFood => "delete_food",
+            ApiError::AddFoodServing => "add_food_serving",
+            ApiError::InvalidRelativeServing => "invalid_relative_serving",
+            ApiError::EditFoodServing => "edit_food_serving",
+            ApiError::DeleteFoodServing => "delete_food_serving",
+            ApiError::EditServingNutrient => "edit_serving_nutrient",
+            ApiError::DeleteServingNutrient => "delete_serving_nutrient",
+            ApiError::QueryDiary => "query_diary",
+            ApiError::AddDiaryEntry => "add_diary_entry",
+            ApiError::EditDiaryEntry => "edit_diary_entry",
+            ApiError::DeleteDiaryEntry => "delete_diary_entry",
+            ApiError::CopyDietToDiary => "copy_diet_to_diary",
+            ApiError::QueryDietTotals => "query_diet_totals",
+            ApiError::QuerySessions => "query_sessions",
+            ApiError::RevokeSession => "revoke_session",
+            ApiError::QueryUser => "query_user",
+            ApiError::EditUser => "edit_user",
+            ApiError::EmailInUse => "email_in_use",
+            ApiError::ChangePassword => "change_password",
+            ApiError::QueryWeightLog => "query_weight_log",
+            ApiError::AddWeightEntry => "add_weight_entry",
+            ApiError::DeleteWeightEntry => "delete_weight_entry",
+            ApiError::DeleteUser => "delete_user",
+            ApiError::DeleteCatalogUser => "delete_catalog_user",
+            ApiError::ExportUser => "export_user",
+            ApiError::InvalidBarcode => "invalid_barcode",
+            ApiError::BarcodeNotFound => "barcode_not_found",
+            ApiError::AddFoodBarcode => "add_food_barcode",
+            ApiError::DeleteFoodBarcode => "delete_food_barcode",
+            ApiError::QueryFavoriteFoods => "query_favorite_foods",
+            ApiError::AddFavoriteFood => "add_favorite_food",
+            ApiError::DeleteFavoriteFood => "delete_favorite_food",
+            ApiError::QueryRecentFoods => "query_recent_foods",
+            ApiError::CreateRecipe => "create_recipe",
+            ApiError::QueryRecipe => "query_recipe",
+            ApiError::EditRecipe => "edit_recipe",
+            ApiError::NotARecipe => "not_a_recipe",
+            ApiError::AddRecipeIngredient => "add_recipe_ingredient",
+            ApiError::EditRecipeIngredient => "edit_recipe_ingredient",
+            ApiError::DeleteRecipeIngredient => "delete_recipe_ingredient",
+            ApiError::RecipeCycle => "recipe_cycle",
+            ApiError::RecipeServing => "recipe_serving",
+            ApiError::ReorderMeals => "reorder_meals",
+            ApiError::ReorderMealServings => "reorder_meal_servings",
+            ApiError::MoveMealServing => "move_meal_serving",
+            ApiError::EditMeal => "edit_meal",
+            ApiError::CopyMeal => "copy_meal",
+            ApiError::MoveMeal => "move_meal",
+            ApiError::ParseNutritionLabel => "parse_nutrition_label",
+            ApiError::UnitMismatch(_) => "unit_mismatch"
+        }
+    }
+
+    /// Validation details, for errors about specific fields of a request
+    pub fn fields(&self) -> &[FieldError] {
+        match self {
+            ApiError::InvalidFields(fields) => fields,
+            _ => &[]
+        }
+    }
 }
 
-impl ToString for ApiError {
-    fn to_string(&self) -> String {
+impl fmt::Display for ApiError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ApiError::RegistrationFailed => "Failed to register user (try again)".to_string(),
-            ApiError::InvalidInput => "Invalid user input (possible problems: data too long, invalid email/birthdate/gender/weight)".to_string(),
-            ApiError::AuthFailed => "User authentication failed (check your credentials)".to_string(),
-            ApiError::NotLoggedIn => "User is not logged in (missing session_id)".to_string(),
-            ApiError::QueryDiets => "Failed to query user diets (try refreshing the page)".to_string(),
-            ApiError::QueryDietNutrition => "Failed to query diet nutrition (try refreshing the page)".to_string(),
-            ApiError::AccessDenied => "Access denied (user cannot access the requested resource)".to_string(),
-            ApiError::QueryNutrients => "Failed to query nutrients (try refreshing the page)".to_string(),
-            ApiError::QueryMeals => "Failed to query meals (try refreshing the page)".to_string(),
-            ApiError::CreateDiet => "Failed to create diet (try again)".to_string(),
-            ApiError::EditDiet => "Failed to edit diet (try again)".to_string(),
-            ApiError::DeleteDiet => "Failed to delete diet (try again)".to_string(),
-            ApiError::CreateMeal => "Failed to create meal (try again)".to_string(),
-            ApiError::DeleteMeal => "Failed to delete meal (try again)".to_string(),
-            ApiError::SearchFoods => "Failed to search foods (try again)".to_string(),
-            ApiError::AddMealServing => "Failed to add serving to meal (try again)".to_string(),
-            ApiError::DeleteMealServing => "Failed to delete serving from meal (try again)".to_string(),
-            ApiError::EditMealServing => "Failed to edit serving (try again)".to_string(),
-            ApiError::QueryFood => "Failed to query food (try again)".to_string(),
-            ApiError::DuplicateDiet => "Failed to duplicate diet (try again)".to_string()
+            ApiError::RegistrationFailed => write!(f, "Failed to register user (try again)"),
+            ApiError::InvalidInput => write!(f, "Invalid user input (possible problems: data too long, invalid email/birthdate/gender/weight)"),
+            ApiError::InvalidFields(fields) => write!(f, "Invalid user input ({})", fields.iter().map(|field| format!("{}: {}", field.field, field.message)).collect::<Vec<_>>().join(", ")),
+            ApiError::AuthFailed => write!(f, "User authentication failed (check your credentials)"),
+            ApiError::NotLoggedIn => write!(f, "User is not logged in (missing session_id)"),
+            ApiError::QueryDiets => write!(f, "Failed to query user diets (try refreshing the page)"),
+            ApiError::QueryDietNutrition => write!(f, "Failed to query diet nutrition (try refreshing the page)"),
+            ApiError::AccessDenied => write!(f, "Access denied (user cannot access the requested resource)"),
+            ApiError::NotFound => write!(f, "Not found (the requested resource does not exist)"),
+            ApiError::QueryNutrients => write!(f, "Failed to query nutrients (try refreshing the page)"),
+            ApiError::QueryMeals => write!(f, "Failed to query meals (try refreshing the page)"),
+            ApiError::CreateDiet => write!(f, "Failed to create diet (try again)"),
+            ApiError::EditDiet => write!(f, "Failed to edit diet (try again)"),
+            ApiError::DeleteDiet => write!(f, "Failed to delete diet (try again)"),
+            ApiError::CreateMeal => write!(f, "Failed to create meal (try again)"),
+            ApiError::DeleteMeal => write!(f, "Failed to delete meal (try again)"),
+            ApiError::SearchFoods => write!(f, "Failed to search foods (try again)"),
+            ApiError::AddMealServing => write!(f, "Failed to add serving to meal (try again)"),
+            ApiError::DeleteMealServing => write!(f, "Failed to delete serving from meal (try again)"),
+            ApiError::EditMealServing => write!(f, "Failed to edit serving (try again)"),
+            ApiError::QueryFood => write!(f, "Failed to query food (try again)"),
+            ApiError::DuplicateDiet => write!(f, "Failed to duplicate diet (try again)"),
+            ApiError::AddDietNutrient => write!(f, "Failed to add nutrient to diet (try again)"),
+            ApiError::DietNutrientExists => write!(f, "The nutrient is already in the diet (edit it instead)"),
+            ApiError::EditDietNutrient => write!(f, "Failed to edit diet nutrient (try again)"),
+            ApiError::DeleteDietNutrient => write!(f, "Failed to delete nutrient from diet (try again)"),
+            ApiError::CreateFood => write!(f, "Failed to create food (try again)"),
+            ApiError::EditFood => write!(f, "Failed to edit food (try again)"),
+            ApiError::DeleteFood => write!(f, "Failed to delete food (try again)"),
+            ApiError::AddFoodServing => write!(f, "Failed to add serving to food (try again)"),
+            ApiError::InvalidRelativeServing => write!(f, "Relative servings must point to a non-relative serving of the same food"),
+            ApiError::EditFoodServing => write!(f, "Failed to edit serving (try again)"),
+            ApiError::DeleteFoodServing => write!(f, "Failed to delete serving from food (try again)"),
+            ApiError::EditServingNutrient => write!(f, "Failed to edit serving nutrient (relative servings have no nutrients of their own)"),
+            ApiError::DeleteServingNutrient => write!(f, "Failed to delete serving nutrient (try again)"),
+            ApiError::QueryDiary => write!(f, "Failed to query diary entries (try refreshing the page)"),
+            ApiError::AddDiaryEntry => write!(f, "Failed to add diary entry (try again)"),
+            ApiError::EditDiaryEntry => write!(f, "Failed to edit diary entry (try again)"),
+            ApiError::DeleteDiaryEntry => write!(f, "Failed to delete diary entry (try again)"),
+            ApiError::CopyDietToDiary => write!(f, "Failed to copy diet meals into the diary (try again)"),
+            ApiError::QueryDietTotals => write!(f, "Failed to calculate diet nutrition totals (try refreshing the page)"),
+            ApiError::QuerySessions => write!(f, "Failed to query sessions (try refreshing the page)"),
+            ApiError::RevokeSession => write!(f, "Failed to revoke session (try again)"),
+            ApiError::QueryUser => write!(f, "Failed to query user account (try refreshing the page)"),
+            ApiError::EditUser => write!(f, "Failed to edit user account (try again)"),
+            ApiError::EmailInUse => write!(f, "The email is already used by another account"),
+            ApiError::ChangePassword => write!(f, "Failed to change password (try again)"),
+            ApiError::QueryWeightLog => write!(f, "Failed to query weight history (try refreshing the page)"),
+            ApiError::AddWeightEntry => write!(f, "Failed to add weight entry (try again)"),
+            ApiError::DeleteWeightEntry => write!(f, "Failed to delete weight entry (try again)"),
+            ApiError::DeleteUser => write!(f, "Failed to delete user account (try again)"),
+            ApiError::DeleteCatalogUser => write!(f, "The account that owns the public food catalog cannot be deleted"),
+            ApiError::ExportUser => write!(f, "Failed to export user data (try again)"),
+            ApiError::InvalidBarcode => write!(f, "Invalid barcode (expected a UPC-A, EAN-13 or EAN-8 code with a valid check digit)"),
+            ApiError::BarcodeNotFound => write!(f, "No food found with this barcode"),
+            ApiError::AddFoodBarcode => write!(f, "Failed to add barcode to food (try again)"),
+            ApiError::DeleteFoodBarcode => write!(f, "Failed to remove barcode from food (try again)"),
+            ApiError::QueryFavoriteFoods => write!(f, "Failed to retrieve favorite foods (try again)"),
+            ApiError::AddFavoriteFood => write!(f, "Failed to add food to favorites (try again)"),
+            ApiError::DeleteFavoriteFood => write!(f, "Failed to remove food from favorites (try again)"),
+            ApiError::QueryRecentFoods => write!(f, "Failed to retrieve recently used foods (try again)"),
+            ApiError::CreateRecipe => write!(f, "Failed to create recipe (try again)"),
+            ApiError::QueryRecipe => write!(f, "Failed to retrieve recipe (try again)"),
+            ApiError::EditRecipe => write!(f, "Failed to edit recipe (try again)"),
+            ApiError::NotARecipe => write!(f, "The food is not a recipe (its nutrients are entered by hand)"),
+            ApiError::AddRecipeIngredient => write!(f, "Failed to add ingredient to recipe (try again)"),
+            ApiError::EditRecipeIngredient => write!(f, "Failed to edit recipe ingredient (try again)"),
+            ApiError::DeleteRecipeIngredient => write!(f, "Failed to remove ingredient from recipe (try again)"),
+            ApiError::RecipeCycle => write!(f, "A recipe cannot contain itself (directly or through another recipe)"),
+            ApiError::RecipeServing => write!(f, "The yield and nutrients of a recipe are calculated from its ingredients (edit the recipe instead)"),
+            ApiError::ReorderMeals => write!(f, "Failed to reorder meals (try again)"),
+            ApiError::ReorderMealServings => write!(f, "Failed to reorder meal foods (try again)"),
+            ApiError::MoveMealServing => write!(f, "Failed to move food to another meal (try again)"),
+            ApiError::EditMeal => write!(f, "Failed to edit meal (try again)"),
+            ApiError::CopyMeal => write!(f, "Failed to copy meal (try again)"),
+            ApiError::MoveMeal => write!(f, "Failed to move meal to another diet (try again)"),
+            ApiError::ParseNutritionLabel => write!(f, "No nutrients found on the nutrition label (expected lines like 'Protein 12g' or 'Protein,12,g')"),
+            ApiError::UnitMismatch(reason) => write!(f, "Incompatible units: {} (amounts can only be converted between units of mass or volume, like g, mg, oz, ml or cup)", reason)
         }
     }
 }
 
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotLoggedIn | ApiError::AuthFailed => StatusCode::UNAUTHORIZED,
+            ApiError::AccessDenied | ApiError::DeleteCatalogUser => StatusCode::FORBIDDEN,
+            ApiError::NotFound | ApiError::BarcodeNotFound => StatusCode::NOT_FOUND,
+            ApiError::DietNutrientExists | ApiError::EmailInUse => StatusCode::CONFLICT,
+            ApiError::InvalidInput |
+            ApiError::InvalidFields(_) |
+            ApiError::InvalidBarcode |
+            ApiError::InvalidRelativeServing |
+            ApiError::NotARecipe |
+            ApiError::RecipeCycle |
+            ApiError::RecipeServing |
+            ApiError::ParseNutritionLabel |
+            ApiError::UnitMismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            // Everything else is a failure of the database (or the server)
+            _ => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ApiResponse::<()>::err(self))
+    }
+}
+
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ApiResponse<T> {
@@ -67,8 +322,12 @@ impl<T> ApiResponse<T> {
         Self::Success(data)
     }
 
-    pub fn err(error : ApiError) -> Self {
-        Self::Failure(ApiResponseError { err: error.to_string() })
+    pub fn err(error : &ApiError) -> Self {
+        Self::Failure(ApiResponseError {
+            err: error.to_string(),
+            code: error.code(),
+            fields: error.fields().to_vec()
+        })
     }
 }
 
@@ -0,0 +1,16 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::NaiveDate;
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct DiaryEntry {
+    pub id : i32,
+    pub user_id : i32,
+    pub entry_date : NaiveDate,
+    pub meal_name : Option<String>,
+    pub serving_id : Option<i32>, /* NULL once the food is deleted, the entry then keeps a copy of it */
+    pub amount : f64,
+    pub food_name : Option<String>,
+    pub serving_unit : Option<String>,
+    pub serving_base : Option<f64>
+}
@@ -1,5 +1,6 @@
 use sqlx::FromRow;
 use serde::Serialize;
+use chrono::{DateTime, Utc};
 
 #[derive(FromRow, Serialize, Debug)]
 pub struct Diet {
@@ -33,7 +34,9 @@ pub struct DietNutrition {
 pub struct Food {
     pub id : i32,
     pub name : String,
-    pub user_id : i32
+    pub user_id : i32,
+    pub fdc_id : Option<i32>, /* FoodData Central ID (only for foods imported from USDA) */
+    pub density : Option<f64> /* g/ml, when it's known */
 }
 
 #[derive(FromRow, Serialize, Debug)]
@@ -45,11 +48,25 @@ pub struct Serving {
     pub relative : Option<i32>
 }
 
+#[derive(FromRow, Serialize, Debug)]
+pub struct FoodFavorite {
+    pub food_id : i32,
+    pub created_at : DateTime<Utc>
+}
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct ServingUsage {
+    pub serving_id : i32,
+    pub use_count : i32,
+    pub last_used : DateTime<Utc>
+}
+
 #[derive(FromRow, Serialize, Debug)]
 pub struct Meal {
     pub id : i32,
     pub diet_id : i32,
-    pub name : String
+    pub name : String,
+    pub position : i32 /* order of the meal in the diet */
 }
 
 #[derive(FromRow, Serialize, Debug)]
@@ -57,7 +74,8 @@ pub struct MealServing {
     pub id : i32,
     pub meal_id : i32,
     pub serving_id : i32,
-    pub amount : f64
+    pub amount : f64,
+    pub position : i32 /* order of the serving in the meal */
 }
 
 #[derive(FromRow, Serialize, Debug)]
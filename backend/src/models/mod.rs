@@ -1,7 +1,13 @@
 mod api_response;
 mod diet;
+mod diary;
+mod recipe;
 mod user;
+mod weight;
 
 pub use api_response::*;
 pub use diet::*;
+pub use diary::*;
+pub use recipe::*;
 pub use user::*;
+pub use weight::*;
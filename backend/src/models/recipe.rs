@@ -0,0 +1,16 @@
+use sqlx::FromRow;
+use serde::Serialize;
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct Recipe {
+    pub food_id : i32,
+    pub serving_id : i32 /* yield serving, which holds the calculated nutrients */
+}
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct RecipeIngredient {
+    pub id : i32,
+    pub recipe_id : i32,
+    pub serving_id : i32,
+    pub amount : f64
+}
@@ -1,6 +1,7 @@
 use sqlx::FromRow;
 use serde::Serialize;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
 
 #[derive(FromRow, Serialize, Debug)]
 pub struct UserAccount {
@@ -11,3 +12,14 @@ pub struct UserAccount {
     pub weight : f64,
     pub birthdate : NaiveDate
 }
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct UserSession {
+    pub id : Uuid,
+    pub user_id : i32,
+    pub public_id : Uuid, /* identifies the session without exposing the session token */
+    pub user_agent : Option<String>,
+    pub created_at : DateTime<Utc>,
+    pub last_seen : DateTime<Utc>,
+    pub expiry_date : DateTime<Utc>
+}
@@ -0,0 +1,11 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::NaiveDate;
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct WeightEntry {
+    pub id : i32,
+    pub user_id : i32,
+    pub entry_date : NaiveDate,
+    pub weight : f64 /* in kilograms */
+}
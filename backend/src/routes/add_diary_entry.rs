@@ -0,0 +1,56 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{add_diary_entry, fetch_diary_entries, fetch_serving_unit},
+        units::convert_serving_amount,
+        validation::*
+    },
+    routes::diary::DiaryResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddDiaryEntryForm {
+    entry_date : NaiveDate,
+    meal_name : Option<String>,
+    serving_id : i32,
+    amount : f64,
+    unit : Option<String> /* any unit compatible with the serving (e.g 'oz' for a serving in grams) */
+}
+
+#[post("/api/add_diary_entry")]
+pub async fn api_add_diary_entry(user : AuthenticatedUser, form : web::Form<AddDiaryEntryForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    let meal_name = form.meal_name.as_ref().filter(|name| !name.is_empty());
+    FieldChecks::new()
+        .check(meal_name.is_none_or(|name| check_name(name)), "meal_name", "must be at most 100 characters")
+        .check(check_serving_amount(form.amount), "amount", "must be positive")
+        .finish()?;
+
+    user.check_access(Resource::Serving(form.serving_id), &dbpool).await?;
+
+    let amount = match form.unit.as_deref().filter(|unit| !unit.is_empty()) {
+        Some(unit) => match fetch_serving_unit(form.serving_id, &dbpool).await {
+            Some(serving_unit) => match convert_serving_amount(form.amount, unit, &serving_unit) {
+                Ok(amount) => amount,
+                Err(e) => return Err(ApiError::UnitMismatch(e.to_string()))
+            },
+            None => return Err(ApiError::AddDiaryEntry)
+        },
+        None => form.amount
+    };
+
+    if add_diary_entry(user.id, form.entry_date, meal_name, form.serving_id, amount, &dbpool).await.is_err() {
+        return Err(ApiError::AddDiaryEntry);
+    }
+
+    match fetch_diary_entries(user.id, form.entry_date, &dbpool).await {
+        Some(entries) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DiaryResponse { entry_date: form.entry_date, entries }))),
+        None => Err(ApiError::QueryDiary)
+    }
+}
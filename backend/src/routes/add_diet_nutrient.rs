@@ -0,0 +1,52 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError, DietNutrient},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{add_diet_nutrient, fetch_diet_info_nutrition, is_foreign_key_violation, is_unique_violation},
+        validation::{FieldChecks, check_intake}
+    },
+    routes::diet_nutrition::DietNutritionResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddDietNutrientForm {
+    diet_id : i32,
+    nutrient_id : i32,
+    min_intake : Option<f64>,
+    max_intake : Option<f64>,
+    relative : bool
+}
+
+#[post("/api/add_diet_nutrient")]
+pub async fn api_add_diet_nutrient(user : AuthenticatedUser, form : web::Form<AddDietNutrientForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
+
+    FieldChecks::from(check_intake(form.min_intake, form.max_intake)).finish()?;
+
+    let diet_nutrient = DietNutrient {
+        diet_id: form.diet_id,
+        nutrient_id: form.nutrient_id,
+        min_intake: form.min_intake,
+        max_intake: form.max_intake,
+        relative: form.relative
+    };
+
+    if let Err(e) = add_diet_nutrient(&diet_nutrient, &dbpool).await {
+        if is_unique_violation(&e) {
+            return Err(ApiError::DietNutrientExists);
+        } else if is_foreign_key_violation(&e) {
+            return Err(ApiError::NotFound); /* unknown nutrient */
+        }
+        return Err(ApiError::AddDietNutrient);
+    }
+
+    match fetch_diet_info_nutrition(form.diet_id, &dbpool).await {
+        Some(nutrition) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DietNutritionResponse { nutrition }))),
+        None => Err(ApiError::QueryDietNutrition)
+    }
+}
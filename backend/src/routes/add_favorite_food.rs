@@ -0,0 +1,27 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::add_favorite_food,
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddFavoriteFoodForm {
+    food_id : i32
+}
+
+#[post("/api/add_favorite_food")]
+pub async fn api_add_favorite_food(user : AuthenticatedUser, form : web::Form<AddFavoriteFoodForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_access(Resource::Food(form.food_id), &dbpool).await?;
+
+    match add_favorite_food(user.id, form.food_id, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::AddFavoriteFood)
+    }
+}
@@ -0,0 +1,39 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        barcode::normalize_barcode,
+        database::{add_food_barcode, fetch_search_food},
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddFoodBarcodeForm {
+    food_id : i32,
+    barcode : String
+}
+
+#[post("/api/add_food_barcode")]
+pub async fn api_add_food_barcode(user : AuthenticatedUser, form : web::Form<AddFoodBarcodeForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Food(form.food_id), &dbpool).await?;
+
+    let code = match normalize_barcode(&form.barcode) {
+        Some(code) => code,
+        None => return Err(ApiError::InvalidBarcode)
+    };
+
+    if add_food_barcode(form.food_id, &code, &dbpool).await.is_err() {
+        return Err(ApiError::AddFoodBarcode);
+    }
+
+    match fetch_search_food(form.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
@@ -0,0 +1,47 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{check_relative_serving, create_serving, fetch_search_food},
+        validation::*
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddFoodServingForm {
+    food_id : i32,
+    unit : String,
+    amount : f64,
+    relative : Option<i32>
+}
+
+#[post("/api/add_food_serving")]
+pub async fn api_add_food_serving(user : AuthenticatedUser, form : web::Form<AddFoodServingForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Food(form.food_id), &dbpool).await?;
+
+    FieldChecks::new()
+        .check(check_serving_unit(&form.unit), "unit", "must be 1 to 100 characters")
+        .check(check_serving_amount(form.amount), "amount", "must be positive")
+        .finish()?;
+
+    if let Some(relative) = form.relative {
+        if !check_relative_serving(form.food_id, None, relative, &dbpool).await {
+            return Err(ApiError::InvalidRelativeServing);
+        }
+    }
+
+    if create_serving(form.food_id, &form.unit, form.amount, form.relative, &dbpool).await.is_err() {
+        return Err(ApiError::AddFoodServing);
+    }
+
+    match fetch_search_food(form.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
@@ -1,12 +1,12 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::{Serialize, Deserialize};
 use log::info;
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_diet_user_id, create_meal},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::create_meal,
     },
     routes::meals::MealInfo
 };
@@ -23,28 +23,15 @@ struct AddMealResponse {
 }
 
 #[post("/api/add_meal")]
-pub async fn api_add_meal(form : web::Form<AddMealForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_add_meal(user : AuthenticatedUser, form : web::Form<AddMealForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<AddMealResponse>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    let diet_user_id = match get_diet_user_id(form.diet_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<&'static str>::err(ApiError::CreateMeal)).respond_to(&req)
-    };
-
-    if user_id != diet_user_id {
-        return web::Json(ApiResponse::<&'static str>::err(ApiError::AccessDenied)).respond_to(&req);
-    }
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
 
     match create_meal(form.diet_id, &form.meal_name, &dbpool).await {
         Some(id) => {
             let meal = MealInfo { id, name: form.meal_name.clone(), foods: vec![] };
-            web::Json(ApiResponse::ok(AddMealResponse { meal })).respond_to(&req)
+            Ok(HttpResponse::Ok().json(ApiResponse::ok(AddMealResponse { meal })))
         }
-        None => web::Json(ApiResponse::<AddMealResponse>::err(ApiError::CreateMeal)).respond_to(&req)
+        None => Err(ApiError::CreateMeal)
     } 
 }
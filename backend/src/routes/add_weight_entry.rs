@@ -0,0 +1,35 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use chrono::{NaiveDate, Utc};
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::add_weight_entry,
+        time::has_date_passed,
+        validation::*
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct AddWeightEntryForm {
+    entry_date : Option<NaiveDate>, /* defaults to today */
+    weight : f64
+}
+
+#[post("/api/add_weight_entry")]
+pub async fn api_add_weight_entry(user : AuthenticatedUser, form : web::Form<AddWeightEntryForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    let entry_date = form.entry_date.unwrap_or_else(|| Utc::now().date_naive());
+    FieldChecks::new()
+        .check(has_date_passed(&entry_date), "entry_date", "must not be in the future")
+        .check(check_weight(form.weight), "weight", "must be positive")
+        .finish()?;
+
+    match add_weight_entry(user.id, entry_date, form.weight, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::AddWeightEntry)
+    }
+}
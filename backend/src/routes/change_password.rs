@@ -0,0 +1,34 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    settings::Settings,
+    utils::{
+        auth::AuthenticatedUser,
+        database::{verify_user_password, change_user_password},
+        validation::{FieldChecks, check_password}
+    }
+};
+
+#[derive(Deserialize)]
+pub struct ChangePasswordForm {
+    current_password : String,
+    new_password : String
+}
+
+#[post("/api/change_password")]
+pub async fn api_change_password(user : AuthenticatedUser, form : web::Form<ChangePasswordForm>, settings : web::Data<Settings>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    if verify_user_password(user.id, &form.current_password, &settings.password_hash, &dbpool).await.is_err() {
+        return Err(ApiError::AuthFailed);
+    }
+
+    FieldChecks::new()
+        .check(check_password(&form.new_password), "new_password", "must be at most 255 characters")
+        .finish()?;
+
+    match change_user_password(user.id, &form.new_password, &user.session_id, &settings.password_hash, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::ChangePassword)
+    }
+}
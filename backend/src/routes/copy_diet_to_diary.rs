@@ -0,0 +1,36 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use chrono::{NaiveDate, Utc};
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{copy_diet_to_diary, fetch_diary_entries},
+    },
+    routes::diary::DiaryResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CopyDietToDiaryForm {
+    diet_id : i32,
+    entry_date : Option<NaiveDate> /* defaults to today */
+}
+
+#[post("/api/copy_diet_to_diary")]
+pub async fn api_copy_diet_to_diary(user : AuthenticatedUser, form : web::Form<CopyDietToDiaryForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
+
+    let entry_date = form.entry_date.unwrap_or_else(|| Utc::now().date_naive());
+
+    if copy_diet_to_diary(user.id, form.diet_id, entry_date, &dbpool).await.is_err() {
+        return Err(ApiError::CopyDietToDiary);
+    }
+
+    match fetch_diary_entries(user.id, entry_date, &dbpool).await {
+        Some(entries) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DiaryResponse { entry_date, entries }))),
+        None => Err(ApiError::QueryDiary)
+    }
+}
@@ -0,0 +1,49 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Serialize, Deserialize};
+use log::{info, error};
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_meal, copy_meal, fetch_meal_info_foods},
+    },
+    routes::meals::MealInfo
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CopyMealForm {
+    meal_id : i32,
+    diet_id : Option<i32> /* destination diet (defaults to the diet of the meal, which duplicates it) */
+}
+
+#[derive(Serialize, Debug)]
+pub struct CopyMealResponse {
+    pub meal : MealInfo
+}
+
+#[post("/api/copy_meal")]
+pub async fn api_copy_meal(user : AuthenticatedUser, form : web::Form<CopyMealForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Meal(form.meal_id), &dbpool).await?;
+    let meal = fetch_meal(form.meal_id, &dbpool).await.ok_or(ApiError::CopyMeal)?;
+
+    let diet_id = form.diet_id.unwrap_or(meal.diet_id);
+    user.check_owner(Resource::Diet(diet_id), &dbpool).await?;
+
+    let new_meal_id = match copy_meal(&meal, diet_id, &dbpool).await {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Failed to run 'copy_meal': {:?}", e);
+            return Err(ApiError::CopyMeal);
+        }
+    };
+
+    match fetch_meal_info_foods(new_meal_id, &dbpool).await {
+        Some(foods) => {
+            let meal = MealInfo { id: new_meal_id, name: meal.name, foods };
+            Ok(HttpResponse::Ok().json(ApiResponse::ok(CopyMealResponse { meal })))
+        }
+        None => Err(ApiError::QueryMeals)
+    }
+}
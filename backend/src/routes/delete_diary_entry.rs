@@ -0,0 +1,33 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_diary_entry, delete_diary_entry, fetch_diary_entries},
+    },
+    routes::diary::DiaryResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteDiaryEntryForm {
+    diary_entry_id : i32
+}
+
+#[post("/api/delete_diary_entry")]
+pub async fn api_delete_diary_entry(user : AuthenticatedUser, form : web::Form<DeleteDiaryEntryForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::DiaryEntry(form.diary_entry_id), &dbpool).await?;
+    let entry = fetch_diary_entry(form.diary_entry_id, &dbpool).await.ok_or(ApiError::DeleteDiaryEntry)?;
+
+    if delete_diary_entry(entry.id, &dbpool).await.is_err() {
+        return Err(ApiError::DeleteDiaryEntry);
+    }
+
+    match fetch_diary_entries(user.id, entry.entry_date, &dbpool).await {
+        Some(entries) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DiaryResponse { entry_date: entry.entry_date, entries }))),
+        None => Err(ApiError::QueryDiary)
+    }
+}
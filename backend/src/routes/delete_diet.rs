@@ -1,12 +1,12 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::Deserialize;
-use log::info;
+use log::{info, error};
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_diet_user_id, delete_diet},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::delete_diet,
     }
 };
 
@@ -16,25 +16,15 @@ pub struct DeleteDietForm {
 }
 
 #[post("/api/delete_diet")]
-pub async fn api_delete_diet(form : web::Form<DeleteDietForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_delete_diet(user : AuthenticatedUser, form : web::Form<DeleteDietForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<&'static str>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    let diet_user_id = match get_diet_user_id(form.diet_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<&'static str>::err(ApiError::DeleteDiet)).respond_to(&req)
-    };
-
-    if user_id != diet_user_id {
-        return web::Json(ApiResponse::<&'static str>::err(ApiError::AccessDenied)).respond_to(&req);
-    }
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
 
     match delete_diet(form.diet_id, &dbpool).await {
-        Ok(_) => web::Json(ApiResponse::ok("OK")).respond_to(&req),
-        Err(_) => web::Json(ApiResponse::<&'static str>::err(ApiError::DeleteDiet)).respond_to(&req)
-    } 
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(e) => {
+            error!("Failed to run 'delete_diet': {:?}", e);
+            Err(ApiError::DeleteDiet)
+        }
+    }
 }
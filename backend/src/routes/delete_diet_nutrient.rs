@@ -0,0 +1,33 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{delete_diet_nutrient, fetch_diet_info_nutrition},
+    },
+    routes::diet_nutrition::DietNutritionResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteDietNutrientForm {
+    diet_id : i32,
+    nutrient_id : i32
+}
+
+#[post("/api/delete_diet_nutrient")]
+pub async fn api_delete_diet_nutrient(user : AuthenticatedUser, form : web::Form<DeleteDietNutrientForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
+
+    if delete_diet_nutrient(form.diet_id, form.nutrient_id, &dbpool).await.is_err() {
+        return Err(ApiError::DeleteDietNutrient);
+    }
+
+    match fetch_diet_info_nutrition(form.diet_id, &dbpool).await {
+        Some(nutrition) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DietNutritionResponse { nutrition }))),
+        None => Err(ApiError::QueryDietNutrition)
+    }
+}
@@ -0,0 +1,27 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::delete_food,
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteFoodForm {
+    food_id : i32
+}
+
+#[post("/api/delete_food")]
+pub async fn api_delete_food(user : AuthenticatedUser, form : web::Form<DeleteFoodForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Food(form.food_id), &dbpool).await?;
+
+    match delete_food(form.food_id, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::DeleteFood)
+    }
+}
@@ -0,0 +1,38 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_serving, is_recipe_serving, delete_serving, fetch_search_food},
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteFoodServingForm {
+    serving_id : i32
+}
+
+#[post("/api/delete_food_serving")]
+pub async fn api_delete_food_serving(user : AuthenticatedUser, form : web::Form<DeleteFoodServingForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Serving(form.serving_id), &dbpool).await?;
+    let serving = fetch_serving(form.serving_id, &dbpool).await.ok_or(ApiError::DeleteFoodServing)?;
+
+    // The yield serving of a recipe is calculated from the ingredients (see '/api/edit_recipe_yield')
+    if is_recipe_serving(serving.id, &dbpool).await != Some(false) {
+        return Err(ApiError::RecipeServing);
+    }
+
+    if delete_serving(serving.id, &dbpool).await.is_err() {
+        return Err(ApiError::DeleteFoodServing);
+    }
+
+    match fetch_search_food(serving.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
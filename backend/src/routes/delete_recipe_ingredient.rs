@@ -0,0 +1,33 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_recipe_ingredient, delete_recipe_ingredient, fetch_recipe_info},
+    },
+    routes::recipe::RecipeResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteRecipeIngredientForm {
+    recipe_ingredient_id : i32
+}
+
+#[post("/api/delete_recipe_ingredient")]
+pub async fn api_delete_recipe_ingredient(user : AuthenticatedUser, form : web::Form<DeleteRecipeIngredientForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::RecipeIngredient(form.recipe_ingredient_id), &dbpool).await?;
+    let ingredient = fetch_recipe_ingredient(form.recipe_ingredient_id, &dbpool).await.ok_or(ApiError::DeleteRecipeIngredient)?;
+
+    if delete_recipe_ingredient(&ingredient, &dbpool).await.is_err() {
+        return Err(ApiError::DeleteRecipeIngredient);
+    }
+
+    match fetch_recipe_info(ingredient.recipe_id, Some(user.id), &dbpool).await {
+        Some(recipe) => Ok(HttpResponse::Ok().json(ApiResponse::ok(RecipeResponse { recipe }))),
+        None => Err(ApiError::QueryRecipe)
+    }
+}
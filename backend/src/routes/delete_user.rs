@@ -0,0 +1,37 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    settings::Settings,
+    utils::{
+        auth::AuthenticatedUser,
+        database::{verify_user_password, delete_user_account, CATALOG_USER_ID},
+        request::add_session_removal_cookies
+    }
+};
+
+#[derive(Deserialize)]
+pub struct DeleteUserForm {
+    password : String
+}
+
+#[post("/api/delete_user")]
+pub async fn api_delete_user(user : AuthenticatedUser, form : web::Form<DeleteUserForm>, settings : web::Data<Settings>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    if user.id == CATALOG_USER_ID {
+        return Err(ApiError::DeleteCatalogUser);
+    }
+
+    if verify_user_password(user.id, &form.password, &settings.password_hash, &dbpool).await.is_err() {
+        return Err(ApiError::AuthFailed);
+    }
+
+    if delete_user_account(user.id, &dbpool).await.is_err() {
+        return Err(ApiError::DeleteUser);
+    }
+
+    // The sessions are gone, so clear the cookies of this client too
+    let mut resp = HttpResponse::Ok().json(ApiResponse::ok("OK"));
+    add_session_removal_cookies(&mut resp);
+    Ok(resp)
+}
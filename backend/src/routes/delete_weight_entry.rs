@@ -0,0 +1,28 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_weight_entry, delete_weight_entry},
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteWeightEntryForm {
+    weight_entry_id : i32
+}
+
+#[post("/api/delete_weight_entry")]
+pub async fn api_delete_weight_entry(user : AuthenticatedUser, form : web::Form<DeleteWeightEntryForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::WeightEntry(form.weight_entry_id), &dbpool).await?;
+    let entry = fetch_weight_entry(form.weight_entry_id, &dbpool).await.ok_or(ApiError::DeleteWeightEntry)?;
+
+    match delete_weight_entry(&entry, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::DeleteWeightEntry)
+    }
+}
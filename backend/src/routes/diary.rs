@@ -0,0 +1,41 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_diary_entries,
+    },
+    routes::meals::MealInfoNutrient
+};
+
+#[derive(Serialize, Debug)]
+pub struct DiaryEntryInfo {
+    pub id : i32,
+    pub meal_name : Option<String>,
+    pub food_id : Option<i32>, /* the food and serving IDs are null once the food is deleted */
+    pub food_name : String,
+    pub serving_id : Option<i32>,
+    pub serving_base : f64,
+    pub serving_amount : f64,
+    pub serving_unit : String,
+    pub base_nutrients : Vec<MealInfoNutrient>
+}
+
+#[derive(Serialize, Debug)]
+pub struct DiaryResponse {
+    pub entry_date : NaiveDate,
+    pub entries : Vec<DiaryEntryInfo>
+}
+
+#[get("/api/diary/{entry_date}")]
+pub async fn api_diary(user : AuthenticatedUser, entry_date : web::Path<NaiveDate>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let entry_date = entry_date.into_inner();
+
+    match fetch_diary_entries(user.id, entry_date, &dbpool).await {
+        Some(entries) => Ok(HttpResponse::Ok().json(ApiResponse::ok(DiaryResponse { entry_date, entries }))),
+        None => Err(ApiError::QueryDiary)
+    }
+}
@@ -0,0 +1,91 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_diet_meals, fetch_meal_info_foods, fetch_nutrients, fetch_diet_info_nutrition, fetch_user_account},
+        nutrition::{sum_food_nutrients, resolve_intake, nutrient_status, NutrientStatus},
+    },
+    routes::meals::{MealInfoFood, MealInfoNutrient}
+};
+
+#[derive(Serialize, Debug)]
+pub struct MealTotals {
+    pub id : i32,
+    pub name : String,
+    pub nutrients : Vec<MealInfoNutrient>
+}
+
+#[derive(Serialize, Debug)]
+pub struct DietTotalNutrient {
+    pub name : String,
+    pub amount : f64,
+    pub unit : String,
+    pub min_amount : Option<f64>, /* already multiplied by the body weight for relative targets */
+    pub max_amount : Option<f64>,
+    pub status : NutrientStatus
+}
+
+#[derive(Serialize, Debug)]
+pub struct DietTotalsResponse {
+    pub meals : Vec<MealTotals>,
+    pub nutrients : Vec<DietTotalNutrient>
+}
+
+#[get("/api/diet_totals/{diet_id}")]
+pub async fn api_diet_totals(user : AuthenticatedUser, diet_id : web::Path<i32>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let diet_id = diet_id.into_inner();
+
+    user.check_owner(Resource::Diet(diet_id), &dbpool).await?;
+
+    let (meals, nutrients, diet_nutrition, user_account) = match (
+        fetch_diet_meals(diet_id, &dbpool).await,
+        fetch_nutrients(&dbpool).await,
+        fetch_diet_info_nutrition(diet_id, &dbpool).await,
+        fetch_user_account(user.id, &dbpool).await
+    ) {
+        (Some(meals), Some(nutrients), Some(diet_nutrition), Some(user_account)) => (meals, nutrients, diet_nutrition, user_account),
+        _ => return Err(ApiError::QueryDietTotals)
+    };
+
+    let mut meal_foods : Vec<(i32, String, Vec<MealInfoFood>)> = vec![];
+    for meal in meals {
+        match fetch_meal_info_foods(meal.id, &dbpool).await {
+            Some(foods) => meal_foods.push((meal.id, meal.name, foods)),
+            None => return Err(ApiError::QueryDietTotals)
+        }
+    }
+
+    let meals_totals : Vec<MealTotals> = meal_foods
+        .iter()
+        .map(|(id, name, foods)| MealTotals { id: *id, name: name.clone(), nutrients: sum_food_nutrients(foods, &nutrients) })
+        .collect();
+
+    let diet_totals = sum_food_nutrients(meal_foods.iter().flat_map(|(_, _, foods)| foods), &nutrients);
+    let nutrient_totals = diet_totals
+        .into_iter()
+        .map(|total| {
+            let target = diet_nutrition.iter().find(|nutrient| nutrient.name == total.name);
+            let (min_amount, max_amount) = match target {
+                Some(target) => (
+                    resolve_intake(target.min_amount, target.relative, user_account.weight),
+                    resolve_intake(target.max_amount, target.relative, user_account.weight)
+                ),
+                None => (None, None)
+            };
+
+            DietTotalNutrient {
+                status: nutrient_status(total.amount, min_amount, max_amount),
+                name: total.name,
+                amount: total.amount,
+                unit: total.unit,
+                min_amount,
+                max_amount
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(DietTotalsResponse { meals: meals_totals, nutrients: nutrient_totals })))
+}
@@ -1,11 +1,11 @@
-use actix_web::{get, Responder, web, HttpRequest};
+use actix_web::{get, web, HttpResponse};
 use serde::Serialize;
 use sqlx::PgPool;
 use crate::{
-    models::{ApiResponse, ApiError, Diet},
+    models::{ApiResponse, ApiError},
     utils::{
+        auth::AuthenticatedUser,
         database::fetch_user_diets,
-        request::get_user_id
     },
     routes::diet_nutrition::DietInfoNutrient
 };
@@ -23,16 +23,10 @@ struct DietsResponse {
 }
 
 #[get("/api/diets")]
-pub async fn api_diets(req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
-    let mut resp = web::Json(ApiResponse::<Vec<Diet>>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    let diets = match fetch_user_diets(user_id, &dbpool).await {
+pub async fn api_diets(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let diets = match fetch_user_diets(user.id, &dbpool).await {
         Ok(d) => d,
-        Err(_) => return web::Json(ApiResponse::<Vec<Diet>>::err(ApiError::QueryDiets)).respond_to(&req)
+        Err(_) => return Err(ApiError::QueryDiets)
     };
     
     let diets_info : Vec<DietInfo> = diets
@@ -40,5 +34,5 @@ pub async fn api_diets(req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Re
         .map(|d| { DietInfo { id: d.id, name: d.name, desired_nutrition: vec![] } })
         .collect();
 
-    web::Json(ApiResponse::ok(DietsResponse { diets: diets_info })).respond_to(&req)
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(DietsResponse { diets: diets_info })))
 }
@@ -1,12 +1,12 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::Deserialize;
-use log::info;
+use log::{info, error};
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_diet_user_id, duplicate_diet},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::duplicate_diet,
     }
 };
 
@@ -17,25 +17,15 @@ pub struct DuplicateDietForm {
 }
 
 #[post("/api/duplicate_diet")]
-pub async fn api_duplicate_diet(form : web::Form<DuplicateDietForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_duplicate_diet(user : AuthenticatedUser, form : web::Form<DuplicateDietForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<&'static str>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
 
-    let diet_user_id = match get_diet_user_id(form.diet_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<&'static str>::err(ApiError::DuplicateDiet)).respond_to(&req)
-    };
-
-    if user_id != diet_user_id {
-        return web::Json(ApiResponse::<&'static str>::err(ApiError::AccessDenied)).respond_to(&req);
+    match duplicate_diet(user.id, form.diet_id, &form.diet_name, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(e) => {
+            error!("Failed to run 'duplicate_diet': {:?}", e);
+            Err(ApiError::DuplicateDiet)
+        }
     }
-
-    match duplicate_diet(user_id, form.diet_id, &form.diet_name, &dbpool).await {
-        Some(_) => web::Json(ApiResponse::ok("OK")).respond_to(&req),
-        None => web::Json(ApiResponse::<&'static str>::err(ApiError::DuplicateDiet)).respond_to(&req)
-    } 
 }
@@ -1,12 +1,12 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::Deserialize;
 use log::info;
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_diet_user_id, edit_diet},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::edit_diet,
     }
 };
 
@@ -17,25 +17,12 @@ pub struct EditDietForm {
 }
 
 #[post("/api/edit_diet")]
-pub async fn api_edit_diet(form : web::Form<EditDietForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_edit_diet(user : AuthenticatedUser, form : web::Form<EditDietForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<&'static str>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    let diet_user_id = match get_diet_user_id(form.diet_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<&'static str>::err(ApiError::EditDiet)).respond_to(&req)
-    };
-
-    if user_id != diet_user_id {
-        return web::Json(ApiResponse::<&'static str>::err(ApiError::AccessDenied)).respond_to(&req);
-    }
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
 
     match edit_diet(form.diet_id, &form.diet_name, &dbpool).await {
-        Some(_) => web::Json(ApiResponse::ok("OK")).respond_to(&req),
-        None => web::Json(ApiResponse::<&'static str>::err(ApiError::EditDiet)).respond_to(&req)
+        Some(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        None => Err(ApiError::EditDiet)
     } 
 }
@@ -0,0 +1,40 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{edit_food, fetch_search_food},
+        validation::{FieldChecks, check_food_name, check_density}
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditFoodForm {
+    food_id : i32,
+    food_name : String,
+    density : Option<f64> /* g/ml */
+}
+
+#[post("/api/edit_food")]
+pub async fn api_edit_food(user : AuthenticatedUser, form : web::Form<EditFoodForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Food(form.food_id), &dbpool).await?;
+
+    FieldChecks::new()
+        .check(check_food_name(&form.food_name), "food_name", "must be 1 to 255 characters")
+        .check(form.density.is_none_or(check_density), "density", "must be positive")
+        .finish()?;
+
+    if edit_food(form.food_id, &form.food_name, form.density, &dbpool).await.is_err() {
+        return Err(ApiError::EditFood);
+    }
+
+    match fetch_search_food(form.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
@@ -0,0 +1,53 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_serving, is_recipe_serving, check_relative_serving, update_serving, fetch_search_food},
+        validation::*
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditFoodServingForm {
+    serving_id : i32,
+    unit : String,
+    amount : f64,
+    relative : Option<i32>
+}
+
+#[post("/api/edit_food_serving")]
+pub async fn api_edit_food_serving(user : AuthenticatedUser, form : web::Form<EditFoodServingForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Serving(form.serving_id), &dbpool).await?;
+    let serving = fetch_serving(form.serving_id, &dbpool).await.ok_or(ApiError::EditFoodServing)?;
+
+    // The yield serving of a recipe is calculated from the ingredients (see '/api/edit_recipe_yield')
+    if is_recipe_serving(serving.id, &dbpool).await != Some(false) {
+        return Err(ApiError::RecipeServing);
+    }
+
+    FieldChecks::new()
+        .check(check_serving_unit(&form.unit), "unit", "must be 1 to 100 characters")
+        .check(check_serving_amount(form.amount), "amount", "must be positive")
+        .finish()?;
+
+    if let Some(relative) = form.relative {
+        if !check_relative_serving(serving.food_id, Some(serving.id), relative, &dbpool).await {
+            return Err(ApiError::InvalidRelativeServing);
+        }
+    }
+
+    if update_serving(serving.id, &form.unit, form.amount, form.relative, &dbpool).await.is_err() {
+        return Err(ApiError::EditFoodServing);
+    }
+
+    match fetch_search_food(serving.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
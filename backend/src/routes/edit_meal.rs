@@ -0,0 +1,33 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::edit_meal,
+        validation::{FieldChecks, check_name}
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditMealForm {
+    meal_id : i32,
+    meal_name : String
+}
+
+#[post("/api/edit_meal")]
+pub async fn api_edit_meal(user : AuthenticatedUser, form : web::Form<EditMealForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Meal(form.meal_id), &dbpool).await?;
+
+    FieldChecks::new()
+        .check(!form.meal_name.is_empty() && check_name(&form.meal_name), "meal_name", "must be 1 to 100 characters")
+        .finish()?;
+
+    match edit_meal(form.meal_id, &form.meal_name, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::EditMeal)
+    }
+}
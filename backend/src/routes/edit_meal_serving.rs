@@ -1,12 +1,13 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::Deserialize;
-use log::info;
+use log::{info, error};
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_meal_serving_user_id, update_meal_serving},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::{update_meal_serving, record_serving_usage, fetch_serving_unit},
+        units::convert_serving_amount
     }
 };
 
@@ -14,29 +15,35 @@ use crate::{
 pub struct EditMealServingForm {
     meal_serving_id : i32,
     serving_id : i32,
-    amount : f64
+    amount : f64,
+    unit : Option<String> /* any unit compatible with the serving (e.g 'oz' for a serving in grams) */
 }
 
 #[post("/api/edit_meal_serving")]
-pub async fn api_edit_meal_serving(form : web::Form<EditMealServingForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_edit_meal_serving(user : AuthenticatedUser, form : web::Form<EditMealServingForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<&'static str>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
+    user.check_owner(Resource::MealServing(form.meal_serving_id), &dbpool).await?;
+    user.check_access(Resource::Serving(form.serving_id), &dbpool).await?;
 
-    let meal_user_id = match get_meal_serving_user_id(form.meal_serving_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<&'static str>::err(ApiError::EditMealServing)).respond_to(&req)
+    let amount = match form.unit.as_deref().filter(|unit| !unit.is_empty()) {
+        Some(unit) => match fetch_serving_unit(form.serving_id, &dbpool).await {
+            Some(serving_unit) => match convert_serving_amount(form.amount, unit, &serving_unit) {
+                Ok(amount) => amount,
+                Err(e) => return Err(ApiError::UnitMismatch(e.to_string()))
+            },
+            None => return Err(ApiError::EditMealServing)
+        },
+        None => form.amount
     };
 
-    if user_id != meal_user_id {
-        return web::Json(ApiResponse::<&'static str>::err(ApiError::AccessDenied)).respond_to(&req);
+    if update_meal_serving(form.meal_serving_id, form.serving_id, amount, &dbpool).await.is_err() {
+        return Err(ApiError::EditMealServing);
+    }
+
+    // Failing to remember the serving usage should not fail the request
+    if let Err(e) = record_serving_usage(user.id, form.serving_id, &dbpool).await {
+        error!("Failed to run 'record_serving_usage': {:?}", e);
     }
 
-    match update_meal_serving(form.meal_serving_id, form.serving_id, form.amount, &dbpool).await {
-        Ok(_) => web::Json(ApiResponse::ok("OK")).respond_to(&req),
-        Err(_) => web::Json(ApiResponse::<&'static str>::err(ApiError::EditMealServing)).respond_to(&req)
-    } 
+    Ok(HttpResponse::Ok().json(ApiResponse::ok("OK")))
 }
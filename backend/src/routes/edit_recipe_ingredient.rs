@@ -0,0 +1,56 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_recipe_ingredient, update_recipe_ingredient, fetch_recipe_info, fetch_serving_unit},
+        units::convert_serving_amount,
+        validation::{FieldChecks, check_serving_amount}
+    },
+    routes::recipe::RecipeResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditRecipeIngredientForm {
+    recipe_ingredient_id : i32,
+    serving_id : i32,
+    amount : f64,
+    unit : Option<String> /* any unit compatible with the serving (e.g 'oz' for a serving in grams) */
+}
+
+#[post("/api/edit_recipe_ingredient")]
+pub async fn api_edit_recipe_ingredient(user : AuthenticatedUser, form : web::Form<EditRecipeIngredientForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::RecipeIngredient(form.recipe_ingredient_id), &dbpool).await?;
+    user.check_access(Resource::Serving(form.serving_id), &dbpool).await?;
+    let ingredient = fetch_recipe_ingredient(form.recipe_ingredient_id, &dbpool).await.ok_or(ApiError::EditRecipeIngredient)?;
+
+    FieldChecks::new()
+        .check(check_serving_amount(form.amount), "amount", "must be positive")
+        .finish()?;
+
+    let amount = match form.unit.as_deref().filter(|unit| !unit.is_empty()) {
+        Some(unit) => match fetch_serving_unit(form.serving_id, &dbpool).await {
+            Some(serving_unit) => match convert_serving_amount(form.amount, unit, &serving_unit) {
+                Ok(amount) => amount,
+                Err(e) => return Err(ApiError::UnitMismatch(e.to_string()))
+            },
+            None => return Err(ApiError::EditRecipeIngredient)
+        },
+        None => form.amount
+    };
+
+    // The recipe can't contain itself (directly or not)
+    let updated = update_recipe_ingredient(&ingredient, form.serving_id, amount, &dbpool).await.map_err(|_| ApiError::EditRecipeIngredient)?;
+    if !updated {
+        return Err(ApiError::RecipeCycle);
+    }
+
+    match fetch_recipe_info(ingredient.recipe_id, Some(user.id), &dbpool).await {
+        Some(recipe) => Ok(HttpResponse::Ok().json(ApiResponse::ok(RecipeResponse { recipe }))),
+        None => Err(ApiError::QueryRecipe)
+    }
+}
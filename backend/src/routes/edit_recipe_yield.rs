@@ -0,0 +1,45 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_recipe, update_recipe_yield, fetch_recipe_info},
+        validation::*
+    },
+    routes::recipe::RecipeResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditRecipeYieldForm {
+    food_id : i32,
+    yield_unit : String,
+    yield_amount : f64
+}
+
+#[post("/api/edit_recipe_yield")]
+pub async fn api_edit_recipe_yield(user : AuthenticatedUser, form : web::Form<EditRecipeYieldForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Food(form.food_id), &dbpool).await?;
+
+    FieldChecks::new()
+        .check(check_serving_unit(&form.yield_unit), "yield_unit", "must be 1 to 100 characters")
+        .check(check_serving_amount(form.yield_amount), "yield_amount", "must be positive")
+        .finish()?;
+
+    let recipe = match fetch_recipe(form.food_id, &dbpool).await {
+        Some(recipe) => recipe,
+        None => return Err(ApiError::NotARecipe)
+    };
+
+    if update_recipe_yield(&recipe, &form.yield_unit, form.yield_amount, &dbpool).await.is_err() {
+        return Err(ApiError::EditRecipe);
+    }
+
+    match fetch_recipe_info(recipe.food_id, Some(user.id), &dbpool).await {
+        Some(recipe) => Ok(HttpResponse::Ok().json(ApiResponse::ok(RecipeResponse { recipe }))),
+        None => Err(ApiError::QueryRecipe)
+    }
+}
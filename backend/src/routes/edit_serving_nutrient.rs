@@ -0,0 +1,63 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_serving, is_recipe_serving, set_serving_nutrient, fetch_search_food, fetch_nutrient},
+        units::convert_nutrient_amount,
+        validation::{FieldChecks, check_nutrient_amount}
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditServingNutrientForm {
+    serving_id : i32,
+    nutrient_id : i32,
+    amount : f64,
+    unit : Option<String> /* 'g', 'mg' or 'µg' (the nutrient's unit by default) */
+}
+
+#[post("/api/edit_serving_nutrient")]
+pub async fn api_edit_serving_nutrient(user : AuthenticatedUser, form : web::Form<EditServingNutrientForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Serving(form.serving_id), &dbpool).await?;
+    let serving = fetch_serving(form.serving_id, &dbpool).await.ok_or(ApiError::EditServingNutrient)?;
+
+    // The yield serving of a recipe is calculated from the ingredients (see '/api/edit_recipe_yield')
+    if is_recipe_serving(serving.id, &dbpool).await != Some(false) {
+        return Err(ApiError::RecipeServing);
+    }
+
+    FieldChecks::new()
+        .check(check_nutrient_amount(form.amount), "amount", "must not be negative")
+        .finish()?;
+
+    // Relative servings take their nutrients from the serving they are relative to
+    if serving.relative.is_some() {
+        return Err(ApiError::EditServingNutrient);
+    }
+
+    let amount = match form.unit.as_deref().filter(|unit| !unit.is_empty()) {
+        Some(unit) => match fetch_nutrient(form.nutrient_id, &dbpool).await {
+            Some(nutrient) => match convert_nutrient_amount(form.amount, unit, &nutrient.unit) {
+                Ok(amount) => amount,
+                Err(e) => return Err(ApiError::UnitMismatch(e.to_string()))
+            },
+            None => return Err(ApiError::EditServingNutrient)
+        },
+        None => form.amount
+    };
+
+    if set_serving_nutrient(serving.id, form.nutrient_id, amount, &dbpool).await.is_err() {
+        return Err(ApiError::EditServingNutrient);
+    }
+
+    match fetch_search_food(serving.food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
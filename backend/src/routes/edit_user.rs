@@ -0,0 +1,41 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::{update_user_account, fetch_user_account, is_unique_violation},
+        validation::*
+    },
+    routes::user::UserResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct EditUserForm {
+    pub name : String,
+    pub birthdate : NaiveDate,
+    pub email : String,
+    pub gender : char,
+    pub weight : f64
+}
+
+#[post("/api/edit_user")]
+pub async fn api_edit_user(user : AuthenticatedUser, form : web::Form<EditUserForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    FieldChecks::from(check_user_profile(&form.name, &form.birthdate, &form.email, form.gender, form.weight)).finish()?;
+
+    if let Err(e) = update_user_account(user.id, &form, &dbpool).await {
+        if is_unique_violation(&e) {
+            return Err(ApiError::EmailInUse);
+        }
+        return Err(ApiError::EditUser);
+    }
+
+    match fetch_user_account(user.id, &dbpool).await {
+        Some(user_account) => Ok(HttpResponse::Ok().json(ApiResponse::ok(UserResponse::from(user_account)))),
+        None => Err(ApiError::QueryUser)
+    }
+}
@@ -0,0 +1,104 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    models::{ApiResponse, ApiError, DiaryEntry, WeightEntry, FoodFavorite, ServingUsage},
+    utils::{
+        auth::AuthenticatedUser,
+        database::{
+            fetch_user_account, fetch_user_diets, fetch_diet_meals, fetch_meal_info_foods, fetch_diet_info_nutrition,
+            fetch_user_sessions, fetch_user_foods, fetch_user_diary, fetch_user_weight_log, fetch_recipe_info,
+            fetch_user_favorites, fetch_user_serving_usage
+        }
+    },
+    routes::{
+        user::UserResponse,
+        diet_nutrition::DietInfoNutrient,
+        meals::MealInfo,
+        food_search::SearchFood,
+        recipe::RecipeInfo,
+        sessions::SessionInfo
+    }
+};
+
+#[derive(Serialize, Debug)]
+struct ExportDiet {
+    id : i32,
+    name : String,
+    desired_nutrition : Vec<DietInfoNutrient>,
+    meals : Vec<MealInfo>
+}
+
+#[derive(Serialize, Debug)]
+struct ExportUserResponse {
+    user : UserResponse,
+    diets : Vec<ExportDiet>,
+    foods : Vec<SearchFood>,
+    recipes : Vec<RecipeInfo>, /* ingredients and yield of the recipes among 'foods' */
+    favorites : Vec<FoodFavorite>,
+    serving_usage : Vec<ServingUsage>,
+    diary : Vec<DiaryEntry>,
+    weight_log : Vec<WeightEntry>,
+    sessions : Vec<SessionInfo>
+}
+
+/// Everything tied to the user, as a single document
+async fn export_user(user_id : i32, session_id : Uuid, dbpool : &PgPool) -> Option<ExportUserResponse> {
+    let user = fetch_user_account(user_id, dbpool).await?.into();
+
+    let mut diets : Vec<ExportDiet> = vec![];
+    for diet in fetch_user_diets(user_id, dbpool).await.ok()? {
+        let mut meals : Vec<MealInfo> = vec![];
+        for meal in fetch_diet_meals(diet.id, dbpool).await? {
+            let foods = fetch_meal_info_foods(meal.id, dbpool).await?;
+            meals.push(MealInfo { id: meal.id, name: meal.name, foods });
+        }
+
+        diets.push(ExportDiet {
+            id: diet.id,
+            desired_nutrition: fetch_diet_info_nutrition(diet.id, dbpool).await?,
+            name: diet.name,
+            meals
+        });
+    }
+
+    let foods = fetch_user_foods(user_id, dbpool).await?;
+    let mut recipes : Vec<RecipeInfo> = vec![];
+    for food in foods.iter().filter(|food| food.recipe) {
+        recipes.push(fetch_recipe_info(food.id, Some(user_id), dbpool).await?);
+    }
+
+    let sessions = fetch_user_sessions(user_id, dbpool)
+        .await?
+        .into_iter()
+        .map(|s| SessionInfo {
+            id: s.public_id,
+            user_agent: s.user_agent,
+            created_at: s.created_at,
+            last_seen: s.last_seen,
+            expiry_date: s.expiry_date,
+            current: s.id == session_id
+        })
+        .collect();
+
+    Some(ExportUserResponse {
+        user,
+        diets,
+        foods,
+        recipes,
+        favorites: fetch_user_favorites(user_id, dbpool).await?,
+        serving_usage: fetch_user_serving_usage(user_id, dbpool).await?,
+        diary: fetch_user_diary(user_id, dbpool).await?,
+        weight_log: fetch_user_weight_log(user_id, dbpool).await?,
+        sessions
+    })
+}
+
+#[get("/api/export_user")]
+pub async fn api_export_user(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    match export_user(user.id, user.session_id, &dbpool).await {
+        Some(export) => Ok(HttpResponse::Ok().json(ApiResponse::ok(export))),
+        None => Err(ApiError::ExportUser)
+    }
+}
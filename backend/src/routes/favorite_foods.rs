@@ -0,0 +1,24 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_favorite_foods,
+    },
+    routes::food_search::SearchFood
+};
+
+#[derive(Serialize, Debug)]
+pub struct FavoriteFoodsResponse {
+    foods : Vec<SearchFood>
+}
+
+#[get("/api/favorite_foods")]
+pub async fn api_favorite_foods(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    match fetch_favorite_foods(user.id, &dbpool).await {
+        Some(foods) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FavoriteFoodsResponse { foods }))),
+        None => Err(ApiError::QueryFavoriteFoods)
+    }
+}
@@ -1,23 +1,24 @@
-use actix_web::{get, Responder, web};
+use actix_web::{get, web};
 use serde::Serialize;
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
-    utils::database::{fetch_search_food},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_search_food
+    },
     routes::food_search::SearchFood
 };
 
 #[derive(Serialize, Debug)]
 pub struct FoodResponse {
-    food : SearchFood
+    pub food : SearchFood
 }
 
 #[get("/api/food/{food_id}")]
-pub async fn api_food(food_id : web::Path<i32>, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_food(user : Option<AuthenticatedUser>, food_id : web::Path<i32>, dbpool : web::Data<PgPool>) -> Result<web::Json<ApiResponse<FoodResponse>>, ApiError> {
     let food_id = food_id.into_inner();
+    let food = fetch_search_food(food_id, user.map(|user| user.id), &dbpool).await.ok_or(ApiError::NotFound)?;
 
-    match fetch_search_food(food_id, &dbpool).await {
-        Some(food) => web::Json(ApiResponse::ok(FoodResponse { food })),
-        None => web::Json(ApiResponse::err(ApiError::QueryFood))
-    }
+    Ok(web::Json(ApiResponse::ok(FoodResponse { food })))
 }
@@ -0,0 +1,21 @@
+use actix_web::{get, web};
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        barcode::normalize_barcode,
+        auth::AuthenticatedUser,
+        database::{find_barcode_food_id, fetch_search_food}
+    },
+    routes::food::FoodResponse
+};
+
+#[get("/api/food/barcode/{code}")]
+pub async fn api_food_barcode(user : Option<AuthenticatedUser>, code : web::Path<String>, dbpool : web::Data<PgPool>) -> Result<web::Json<ApiResponse<FoodResponse>>, ApiError> {
+    let user_id = user.map(|user| user.id);
+    let code = normalize_barcode(&code).ok_or(ApiError::InvalidBarcode)?;
+    let food_id = find_barcode_food_id(&code, user_id, &dbpool).await.ok_or(ApiError::BarcodeNotFound)?;
+    let food = fetch_search_food(food_id, user_id, &dbpool).await.ok_or(ApiError::QueryFood)?;
+
+    Ok(web::Json(ApiResponse::ok(FoodResponse { food })))
+}
@@ -1,9 +1,13 @@
 use actix_web::{get, Responder, web};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use crate::{
-    models::{ApiResponse, ApiError},
-    utils::database::search_foods,
+    models::{ApiResponse, ApiError, FieldError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::search_foods,
+        validation::FieldChecks
+    },
     routes::meals::MealInfoNutrient
 };
 
@@ -20,20 +24,162 @@ pub struct SearchFoodServing {
 pub struct SearchFood {
     pub id : i32,
     pub name : String,
-    pub servings : Vec<SearchFoodServing>
+    pub servings : Vec<SearchFoodServing>,
+    pub barcodes : Vec<String>,
+    pub density : Option<f64>, /* g/ml, when it's known */
+    pub recipe : bool /* the nutrients are calculated from the ingredients (see '/api/recipe/{food_id}') */
+}
+
+/// Maximum amount of foods per page
+const SEARCH_MAX_LIMIT : i64 = 50;
+
+fn default_page() -> i64 { 1 }
+fn default_limit() -> i64 { 10 }
+
+/// What the nutrient amounts of the filters and the sorting refer to
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum NutrientBasis {
+    #[default]
+    #[serde(rename = "100g")]
+    Per100g, /* only foods with a base serving in grams */
+    #[serde(rename = "serving")]
+    PerServing /* base serving of the food */
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FoodOwner {
+    #[default]
+    All,
+    Catalog, /* public catalog */
+    Mine /* private foods of the user */
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FoodSource {
+    #[default]
+    All,
+    Usda, /* imported from FoodData Central */
+    Custom /* created by hand */
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NutrientFilter {
+    pub nutrient_id : i32,
+    pub min : Option<f64>,
+    pub max : Option<f64>
+}
+
+impl NutrientFilter {
+    /// Parses a list of filters in the format '<nutrient_id>:<min>:<max>,...', where 'min' or 'max'
+    /// may be empty (e.g '1:20:,27::200' is at least 20 of nutrient 1, and at most 200 of nutrient 27)
+    pub fn parse_list(list : &str) -> Option<Vec<Self>> {
+        list.split(',')
+            .filter(|filter| !filter.is_empty())
+            .map(|filter| {
+                let mut parts = filter.split(':');
+                let nutrient_id = parts.next()?.trim().parse().ok()?;
+                let mut bound = || -> Option<Option<f64>> {
+                    match parts.next()?.trim() {
+                        "" => Some(None),
+                        amount => amount.parse::<f64>().ok().filter(|amount| amount.is_finite()).map(Some)
+                    }
+                };
+                let (min, max) = (bound()?, bound()?);
+
+                if parts.next().is_some() || min.zip(max).is_some_and(|(min, max)| min > max) {
+                    return None;
+                }
+
+                Some(Self { nutrient_id, min, max })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FoodSearchFilters {
+    pub nutrients : Vec<NutrientFilter>,
+    pub basis : NutrientBasis,
+    pub owner : FoodOwner,
+    pub source : FoodSource,
+    pub sort_nutrient : Option<i32>, /* sort by the amount of this nutrient, instead of relevance */
+    pub sort_order : SortOrder,
+    pub personalize : bool /* rank the favorites and the most used foods of the user first */
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FoodSearchQuery {
+    #[serde(default = "default_page")]
+    page : i64,
+    #[serde(default = "default_limit")]
+    limit : i64,
+    nutrients : Option<String>,
+    #[serde(default)]
+    basis : NutrientBasis,
+    #[serde(default)]
+    owner : FoodOwner,
+    #[serde(default)]
+    source : FoodSource,
+    sort_nutrient : Option<i32>,
+    #[serde(default)]
+    sort_order : SortOrder,
+    #[serde(default)]
+    personalize : bool
 }
 
 #[derive(Serialize, Debug)]
 pub struct FoodSearchResponse {
-    matches : Vec<SearchFood>
+    matches : Vec<SearchFood>,
+    total : i64,
+    page : i64,
+    limit : i64
+}
+
+async fn food_search(food_name : Option<&str>, query : &FoodSearchQuery, user_id : Option<i32>, dbpool : &PgPool) -> Result<web::Json<ApiResponse<FoodSearchResponse>>, ApiError> {
+    // The offset of the page must fit in an i64 too
+    FieldChecks::new()
+        .check(query.page >= 1 && query.page.checked_mul(query.limit).is_some(), "page", "must be positive and not too large")
+        .check((1..=SEARCH_MAX_LIMIT).contains(&query.limit), "limit", &format!("must be 1 to {}", SEARCH_MAX_LIMIT))
+        .finish()?;
+
+    let nutrients = match query.nutrients.as_deref().map(NutrientFilter::parse_list) {
+        Some(Some(nutrients)) => nutrients,
+        Some(None) => return Err(ApiError::InvalidFields(vec![FieldError::new("nutrients", "must be a list of filters like '1:20:,27::200'")])),
+        None => vec![]
+    };
+
+    let filters = FoodSearchFilters {
+        nutrients,
+        basis: query.basis,
+        owner: query.owner,
+        source: query.source,
+        sort_nutrient: query.sort_nutrient,
+        sort_order: query.sort_order,
+        personalize: query.personalize
+    };
+
+    let (matches, total) = search_foods(food_name, user_id, &filters, query.page, query.limit, dbpool).await.ok_or(ApiError::SearchFoods)?;
+
+    Ok(web::Json(ApiResponse::ok(FoodSearchResponse { matches, total, page: query.page, limit: query.limit })))
 }
 
 #[get("/api/food_search/{food_name}")]
-pub async fn api_food_search(food_name : web::Path<String>, dbpool : web::Data<PgPool>) -> impl Responder {
-    let food_name = food_name.into_inner();
+pub async fn api_food_search(user : Option<AuthenticatedUser>, food_name : web::Path<String>, query : web::Query<FoodSearchQuery>, dbpool : web::Data<PgPool>) -> impl Responder {
+    food_search(Some(&food_name), &query, user.map(|user| user.id), &dbpool).await
+}
 
-    match search_foods(&food_name, &dbpool).await {
-        Some(matches) => web::Json(ApiResponse::ok(FoodSearchResponse { matches })),
-        None => web::Json(ApiResponse::err(ApiError::SearchFoods))
-    }
+/// Same as 'api_food_search', but only with the filters (e.g all foods with at least 20g of protein)
+#[get("/api/food_search")]
+pub async fn api_food_browse(user : Option<AuthenticatedUser>, query : web::Query<FoodSearchQuery>, dbpool : web::Data<PgPool>) -> impl Responder {
+    food_search(None, &query, user.map(|user| user.id), &dbpool).await
 }
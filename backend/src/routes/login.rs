@@ -1,10 +1,14 @@
-use actix_web::{post, Responder, web};
-use serde::{Serialize, Deserialize};
+use actix_web::{http::header, post, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
 use log::info;
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
-    utils::database::authenticate_user
+    settings::Settings,
+    utils::{
+        database::authenticate_user,
+        request::{add_session_cookies, new_session_max_age}
+    }
 };
 
 #[derive(Deserialize, Debug)]
@@ -13,18 +17,13 @@ pub struct LoginForm {
     pub password : String
 }
 
-#[derive(Serialize)]
-struct LoginResponse {
-    pub session_id : String
-}
-
 #[post("/api/login")]
-pub async fn api_login(form : web::Form<LoginForm>, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_login(form : web::Form<LoginForm>, req : HttpRequest, settings : web::Data<Settings>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let session_id = match authenticate_user(&form, &dbpool).await {
-        Ok(session_id) => session_id,
-        Err(_) => return web::Json(ApiResponse::err(ApiError::AuthFailed))
-    };
+    let user_agent = req.headers().get(header::USER_AGENT).and_then(|value| value.to_str().ok());
+    let session_id = authenticate_user(&form, user_agent, &settings, &dbpool).await.map_err(|_| ApiError::AuthFailed)?;
 
-    web::Json(ApiResponse::ok(LoginResponse { session_id }))
+    let mut resp = HttpResponse::Ok().json(ApiResponse::ok("OK"));
+    add_session_cookies(&mut resp, &session_id, new_session_max_age(&settings.session), &settings.session);
+    Ok(resp)
 }
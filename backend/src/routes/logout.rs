@@ -1,24 +1,19 @@
-use actix_web::{
-    http::StatusCode,
-    post, Responder, web, HttpRequest, HttpResponse
-};
+use actix_web::{post, web, HttpResponse};
 use sqlx::PgPool;
 use crate::{
-    models::{ApiResponse, ApiError},
+    models::ApiResponse,
     utils::{
-        request::get_session_id,
+        auth::AuthenticatedUser,
+        request::add_session_removal_cookies,
         database::delete_session
     }
 };
 
 #[post("/api/logout")]
-pub async fn api_logout(req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
-    let session_id = match get_session_id(&req, &mut HttpResponse::new(StatusCode::OK)) {
-        Some(session_id) => session_id,
-        None => return web::Json(ApiResponse::err(ApiError::NotLoggedIn))
-    };
-
-    delete_session(session_id, &dbpool).await.ok();
+pub async fn api_logout(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> HttpResponse {
+    delete_session(user.session_id, &dbpool).await.ok();
 
-    web::Json(ApiResponse::ok("OK"))
+    let mut resp = HttpResponse::Ok().json(ApiResponse::ok("OK"));
+    add_session_removal_cookies(&mut resp);
+    resp
 }
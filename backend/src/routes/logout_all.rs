@@ -0,0 +1,21 @@
+use actix_web::{post, web, HttpResponse};
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::delete_user_sessions,
+        request::add_session_removal_cookies
+    }
+};
+
+#[post("/api/logout_all")]
+pub async fn api_logout_all(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    if delete_user_sessions(user.id, &dbpool).await.is_err() {
+        return Err(ApiError::RevokeSession);
+    }
+
+    let mut resp = HttpResponse::Ok().json(ApiResponse::ok("OK"));
+    add_session_removal_cookies(&mut resp);
+    Ok(resp)
+}
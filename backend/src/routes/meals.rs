@@ -1,15 +1,15 @@
-use actix_web::{get, Responder, web, HttpRequest};
+use actix_web::{get, web, HttpResponse};
 use serde::Serialize;
 use sqlx::{PgPool, FromRow};
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
-        database::{get_diet_user_id, fetch_diet_meals, fetch_meal_info_foods},
-        request::get_user_id
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_diet_meals, fetch_meal_info_foods},
     }
 };
 
-#[derive(Serialize, FromRow, Debug)]
+#[derive(Serialize, FromRow, Debug, Clone)]
 pub struct MealInfoNutrient {
     pub name : String,
     pub amount : f64,
@@ -41,38 +41,25 @@ struct MealsResponse {
 }
 
 #[get("/api/meals/{diet_id}")]
-pub async fn api_meals(diet_id : web::Path<i32>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_meals(user : AuthenticatedUser, diet_id : web::Path<i32>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     let diet_id = diet_id.into_inner();
 
-    let mut resp = web::Json(ApiResponse::<MealsResponse>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    let diet_user_id = match get_diet_user_id(diet_id, &dbpool).await {
-        Some(id) => id,
-        None => return web::Json(ApiResponse::<MealsResponse>::err(ApiError::QueryMeals)).respond_to(&req)
-    };
-
-    if user_id != diet_user_id {
-        return web::Json(ApiResponse::<MealsResponse>::err(ApiError::AccessDenied)).respond_to(&req);
-    }
+    user.check_owner(Resource::Diet(diet_id), &dbpool).await?;
 
     let meals = match fetch_diet_meals(diet_id, &dbpool).await {
         Some(meals) => meals,
-        None => return web::Json(ApiResponse::<MealsResponse>::err(ApiError::QueryMeals)).respond_to(&req)
+        None => return Err(ApiError::QueryMeals)
     };
 
     let mut meals_info : Vec<MealInfo> = vec![];
     for meal in &meals {
         let meal_info_foods = match fetch_meal_info_foods(meal.id, &dbpool).await {
             Some(foods) => foods,
-            None => return web::Json(ApiResponse::<MealsResponse>::err(ApiError::QueryMeals)).respond_to(&req)
+            None => return Err(ApiError::QueryMeals)
         };
 
         meals_info.push(MealInfo { id: meal.id, name: meal.name.clone(), foods: meal_info_foods });
     }
 
-    web::Json(ApiResponse::ok(MealsResponse { meals: meals_info })).respond_to(&req)
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(MealsResponse { meals: meals_info })))
 }
@@ -17,6 +17,54 @@ pub mod delete_meal_serving;
 pub mod edit_meal_serving;
 pub mod food;
 pub mod duplicate_diet;
+pub mod add_diet_nutrient;
+pub mod edit_diet_nutrient;
+pub mod delete_diet_nutrient;
+pub mod new_food;
+pub mod edit_food;
+pub mod delete_food;
+pub mod add_food_serving;
+pub mod edit_food_serving;
+pub mod delete_food_serving;
+pub mod edit_serving_nutrient;
+pub mod delete_serving_nutrient;
+pub mod diary;
+pub mod add_diary_entry;
+pub mod edit_diary_entry;
+pub mod delete_diary_entry;
+pub mod copy_diet_to_diary;
+pub mod diet_totals;
+pub mod sessions;
+pub mod revoke_session;
+pub mod logout_all;
+pub mod edit_user;
+pub mod change_password;
+pub mod weight_log;
+pub mod add_weight_entry;
+pub mod delete_weight_entry;
+pub mod weight_trend;
+pub mod delete_user;
+pub mod export_user;
+pub mod food_barcode;
+pub mod add_food_barcode;
+pub mod delete_food_barcode;
+pub mod favorite_foods;
+pub mod add_favorite_food;
+pub mod delete_favorite_food;
+pub mod recent_foods;
+pub mod recipe;
+pub mod new_recipe;
+pub mod edit_recipe_yield;
+pub mod add_recipe_ingredient;
+pub mod edit_recipe_ingredient;
+pub mod delete_recipe_ingredient;
+pub mod reorder_meals;
+pub mod reorder_meal_servings;
+pub mod move_meal_serving;
+pub mod edit_meal;
+pub mod copy_meal;
+pub mod move_meal;
+pub mod parse_nutrition_label;
 
 pub use register::api_register;
 pub use login::api_login;
@@ -31,9 +79,57 @@ pub use edit_diet::api_edit_diet;
 pub use delete_diet::api_delete_diet;
 pub use add_meal::api_add_meal;
 pub use delete_meal::api_delete_meal;
-pub use food_search::api_food_search;
+pub use food_search::{api_food_search, api_food_browse};
 pub use add_meal_serving::api_add_meal_serving;
 pub use delete_meal_serving::api_delete_meal_serving;
 pub use edit_meal_serving::api_edit_meal_serving;
 pub use food::api_food;
 pub use duplicate_diet::api_duplicate_diet;
+pub use add_diet_nutrient::api_add_diet_nutrient;
+pub use edit_diet_nutrient::api_edit_diet_nutrient;
+pub use delete_diet_nutrient::api_delete_diet_nutrient;
+pub use new_food::api_new_food;
+pub use edit_food::api_edit_food;
+pub use delete_food::api_delete_food;
+pub use add_food_serving::api_add_food_serving;
+pub use edit_food_serving::api_edit_food_serving;
+pub use delete_food_serving::api_delete_food_serving;
+pub use edit_serving_nutrient::api_edit_serving_nutrient;
+pub use delete_serving_nutrient::api_delete_serving_nutrient;
+pub use diary::api_diary;
+pub use add_diary_entry::api_add_diary_entry;
+pub use edit_diary_entry::api_edit_diary_entry;
+pub use delete_diary_entry::api_delete_diary_entry;
+pub use copy_diet_to_diary::api_copy_diet_to_diary;
+pub use diet_totals::api_diet_totals;
+pub use sessions::api_sessions;
+pub use revoke_session::api_revoke_session;
+pub use logout_all::api_logout_all;
+pub use edit_user::api_edit_user;
+pub use change_password::api_change_password;
+pub use weight_log::api_weight_log;
+pub use add_weight_entry::api_add_weight_entry;
+pub use delete_weight_entry::api_delete_weight_entry;
+pub use weight_trend::api_weight_trend;
+pub use delete_user::api_delete_user;
+pub use export_user::api_export_user;
+pub use food_barcode::api_food_barcode;
+pub use add_food_barcode::api_add_food_barcode;
+pub use delete_food_barcode::api_delete_food_barcode;
+pub use favorite_foods::api_favorite_foods;
+pub use add_favorite_food::api_add_favorite_food;
+pub use delete_favorite_food::api_delete_favorite_food;
+pub use recent_foods::api_recent_foods;
+pub use recipe::api_recipe;
+pub use new_recipe::api_new_recipe;
+pub use edit_recipe_yield::api_edit_recipe_yield;
+pub use add_recipe_ingredient::api_add_recipe_ingredient;
+pub use edit_recipe_ingredient::api_edit_recipe_ingredient;
+pub use delete_recipe_ingredient::api_delete_recipe_ingredient;
+pub use reorder_meals::api_reorder_meals;
+pub use reorder_meal_servings::api_reorder_meal_servings;
+pub use move_meal_serving::api_move_meal_serving;
+pub use edit_meal::api_edit_meal;
+pub use copy_meal::api_copy_meal;
+pub use move_meal::api_move_meal;
+pub use parse_nutrition_label::api_parse_nutrition_label;
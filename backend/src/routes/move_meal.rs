@@ -0,0 +1,29 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::move_meal,
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct MoveMealForm {
+    meal_id : i32,
+    diet_id : i32 /* destination diet */
+}
+
+#[post("/api/move_meal")]
+pub async fn api_move_meal(user : AuthenticatedUser, form : web::Form<MoveMealForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Meal(form.meal_id), &dbpool).await?;
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
+
+    match move_meal(form.meal_id, form.diet_id, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::MoveMeal)
+    }
+}
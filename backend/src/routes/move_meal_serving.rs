@@ -0,0 +1,41 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_meal, fetch_meal_serving, move_meal_serving},
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct MoveMealServingForm {
+    meal_serving_id : i32,
+    meal_id : i32, /* destination meal, in the same diet */
+    position : Option<usize> /* position in the destination meal (defaults to the end) */
+}
+
+#[post("/api/move_meal_serving")]
+pub async fn api_move_meal_serving(user : AuthenticatedUser, form : web::Form<MoveMealServingForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::MealServing(form.meal_serving_id), &dbpool).await?;
+    user.check_owner(Resource::Meal(form.meal_id), &dbpool).await?;
+    let meal_serving = fetch_meal_serving(form.meal_serving_id, &dbpool).await.ok_or(ApiError::MoveMealServing)?;
+
+    let (source_meal, target_meal) = match (fetch_meal(meal_serving.meal_id, &dbpool).await, fetch_meal(form.meal_id, &dbpool).await) {
+        (Some(source_meal), Some(target_meal)) => (source_meal, target_meal),
+        _ => return Err(ApiError::MoveMealServing)
+    };
+
+    // Meal servings can only be moved within a diet
+    if source_meal.diet_id != target_meal.diet_id {
+        return Err(ApiError::AccessDenied);
+    }
+
+    match move_meal_serving(&meal_serving, target_meal.id, form.position, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::MoveMealServing)
+    }
+}
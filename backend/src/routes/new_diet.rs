@@ -1,12 +1,12 @@
-use actix_web::{post, Responder, web, HttpRequest};
+use actix_web::{post, web, HttpResponse};
 use serde::Deserialize;
-use log::info;
+use log::{info, error};
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
     utils::{
+        auth::AuthenticatedUser,
         database::create_diet,
-        request::get_user_id
     }
 };
 
@@ -16,16 +16,13 @@ pub struct NewDietForm {
 }
 
 #[post("/api/new_diet")]
-pub async fn api_new_diet(form : web::Form<NewDietForm>, req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_new_diet(user : AuthenticatedUser, form : web::Form<NewDietForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     info!("{:?}", form);
-    let mut resp = web::Json(ApiResponse::<&'static str>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
-
-    match create_diet(user_id, &form.diet_name, &dbpool).await {
-        Some(_) => web::Json(ApiResponse::ok("OK")).respond_to(&req),
-        None => web::Json(ApiResponse::<&'static str>::err(ApiError::CreateDiet)).respond_to(&req)
-    } 
+    match create_diet(user.id, &form.diet_name, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(e) => {
+            error!("Failed to run 'create_diet': {:?}", e);
+            Err(ApiError::CreateDiet)
+        }
+    }
 }
@@ -0,0 +1,50 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::{create_food, fetch_search_food},
+        validation::*,
+        barcode::normalize_barcode
+    },
+    routes::food::FoodResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct NewFoodForm {
+    food_name : String,
+    serving_unit : String,
+    serving_amount : f64,
+    barcode : Option<String>
+}
+
+#[post("/api/new_food")]
+pub async fn api_new_food(user : AuthenticatedUser, form : web::Form<NewFoodForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    FieldChecks::new()
+        .check(check_food_name(&form.food_name), "food_name", "must be 1 to 255 characters")
+        .check(check_serving_unit(&form.serving_unit), "serving_unit", "must be 1 to 100 characters")
+        .check(check_serving_amount(form.serving_amount), "serving_amount", "must be positive")
+        .finish()?;
+
+    let barcode = match form.barcode.as_deref().filter(|code| !code.is_empty()) {
+        Some(code) => match normalize_barcode(code) {
+            Some(code) => Some(code),
+            None => return Err(ApiError::InvalidBarcode)
+        },
+        None => None
+    };
+
+    let food_id = match create_food(user.id, &form.food_name, &form.serving_unit, form.serving_amount, barcode.as_ref(), &dbpool).await {
+        Ok(id) => id,
+        Err(_) => return Err(ApiError::CreateFood)
+    };
+
+    match fetch_search_food(food_id, Some(user.id), &dbpool).await {
+        Some(food) => Ok(HttpResponse::Ok().json(ApiResponse::ok(FoodResponse { food }))),
+        None => Err(ApiError::QueryFood)
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::{create_recipe, fetch_recipe_info},
+        validation::*
+    },
+    routes::recipe::RecipeResponse
+};
+
+#[derive(Deserialize, Debug)]
+pub struct NewRecipeForm {
+    recipe_name : String,
+    yield_unit : String, /* e.g 'g' for the total weight, or 'portion' */
+    yield_amount : f64
+}
+
+#[post("/api/new_recipe")]
+pub async fn api_new_recipe(user : AuthenticatedUser, form : web::Form<NewRecipeForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    FieldChecks::new()
+        .check(check_food_name(&form.recipe_name), "recipe_name", "must be 1 to 255 characters")
+        .check(check_serving_unit(&form.yield_unit), "yield_unit", "must be 1 to 100 characters")
+        .check(check_serving_amount(form.yield_amount), "yield_amount", "must be positive")
+        .finish()?;
+
+    let food_id = match create_recipe(user.id, &form.recipe_name, &form.yield_unit, form.yield_amount, &dbpool).await {
+        Ok(id) => id,
+        Err(_) => return Err(ApiError::CreateRecipe)
+    };
+
+    match fetch_recipe_info(food_id, Some(user.id), &dbpool).await {
+        Some(recipe) => Ok(HttpResponse::Ok().json(ApiResponse::ok(RecipeResponse { recipe }))),
+        None => Err(ApiError::QueryRecipe)
+    }
+}
@@ -1,4 +1,4 @@
-use actix_web::{get, Responder, web};
+use actix_web::{get, web};
 use serde::Serialize;
 use sqlx::PgPool;
 use crate::{
@@ -7,16 +7,13 @@ use crate::{
 };
 
 #[derive(Serialize, Debug)]
-struct NutrientsResponse {
-    nutrients : Vec<Nutrient>
+pub struct NutrientsResponse {
+    pub nutrients : Vec<Nutrient>
 }
 
 #[get("/api/nutrients")]
-pub async fn api_nutrients(dbpool : web::Data<PgPool>) -> impl Responder {
-    let nutrients = match fetch_nutrients(&dbpool).await {
-        Some(nutrients) => nutrients,
-        None => return web::Json(ApiResponse::err(ApiError::QueryNutrients))
-    };
+pub async fn api_nutrients(dbpool : web::Data<PgPool>) -> Result<web::Json<ApiResponse<NutrientsResponse>>, ApiError> {
+    let nutrients = fetch_nutrients(&dbpool).await.ok_or(ApiError::QueryNutrients)?;
 
-    web::Json(ApiResponse::ok(NutrientsResponse { nutrients }))
+    Ok(web::Json(ApiResponse::ok(NutrientsResponse { nutrients })))
 }
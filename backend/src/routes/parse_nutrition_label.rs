@@ -0,0 +1,74 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Serialize, Deserialize};
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_nutrients,
+        label::{parse_nutrition_label, LabelNutrient, LABEL_DEFAULT_SERVING},
+        validation::{FieldChecks, check_food_name}
+    }
+};
+
+/// Maximum length of the label text
+const LABEL_MAX_LENGTH : usize = 10000;
+
+#[derive(Deserialize, Debug)]
+pub struct ParseNutritionLabelForm {
+    label : String, /* pasted label text, or CSV rows of name, amount and unit */
+    food_name : Option<String>
+}
+
+#[derive(Serialize, Debug)]
+pub struct DraftServing {
+    pub amount : f64,
+    pub unit : String,
+    pub nutrients : Vec<LabelNutrient>
+}
+
+/// Food read from a label, which is not saved until the user confirms it (see '/api/new_food')
+#[derive(Serialize, Debug)]
+pub struct DraftFood {
+    pub name : String,
+    pub servings : Vec<DraftServing>
+}
+
+#[derive(Serialize, Debug)]
+pub struct NutritionLabelResponse {
+    food : DraftFood,
+    unmatched : Vec<String>
+}
+
+#[post("/api/parse_nutrition_label")]
+pub async fn api_parse_nutrition_label(_user : AuthenticatedUser, form : web::Form<ParseNutritionLabelForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    let food_name = form.food_name.clone().unwrap_or_default();
+    FieldChecks::new()
+        .check(form.label.len() <= LABEL_MAX_LENGTH, "label", &format!("must be at most {} characters", LABEL_MAX_LENGTH))
+        .check(food_name.is_empty() || check_food_name(&food_name), "food_name", "must be at most 255 characters")
+        .finish()?;
+
+    let nutrients = match fetch_nutrients(&dbpool).await {
+        Some(nutrients) => nutrients,
+        None => return Err(ApiError::QueryNutrients)
+    };
+
+    let label = parse_nutrition_label(&form.label, &nutrients);
+    if label.nutrients.is_empty() {
+        return Err(ApiError::ParseNutritionLabel);
+    }
+
+    let (amount, unit) = match label.serving {
+        Some(serving) => (serving.amount, serving.unit),
+        None => (LABEL_DEFAULT_SERVING.0, LABEL_DEFAULT_SERVING.1.to_string())
+    };
+
+    let food = DraftFood {
+        name: food_name,
+        servings: vec![DraftServing { amount, unit, nutrients: label.nutrients }]
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(NutritionLabelResponse { food, unmatched: label.unmatched })))
+}
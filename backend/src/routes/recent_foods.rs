@@ -0,0 +1,38 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Serialize, Deserialize};
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_recent_foods,
+        validation::FieldChecks
+    },
+    routes::food_search::SearchFood
+};
+
+const DEFAULT_LIMIT : i64 = 10;
+const MAX_LIMIT : i64 = 50;
+
+#[derive(Deserialize, Debug)]
+pub struct RecentFoodsQuery {
+    limit : Option<i64>
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecentFoodsResponse {
+    foods : Vec<SearchFood>
+}
+
+#[get("/api/recent_foods")]
+pub async fn api_recent_foods(user : AuthenticatedUser, query : web::Query<RecentFoodsQuery>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    FieldChecks::new()
+        .check((1..=MAX_LIMIT).contains(&limit), "limit", &format!("must be 1 to {}", MAX_LIMIT))
+        .finish()?;
+
+    match fetch_recent_foods(user.id, limit, &dbpool).await {
+        Some(foods) => Ok(HttpResponse::Ok().json(ApiResponse::ok(RecentFoodsResponse { foods }))),
+        None => Err(ApiError::QueryRecentFoods)
+    }
+}
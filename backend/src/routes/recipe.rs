@@ -0,0 +1,43 @@
+use actix_web::{get, web};
+use serde::Serialize;
+use sqlx::{PgPool, FromRow};
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_recipe_info
+    }
+};
+
+#[derive(Serialize, FromRow, Debug)]
+pub struct RecipeInfoIngredient {
+    pub id : i32,
+    pub food_id : i32,
+    pub food_name : String,
+    pub serving_id : i32,
+    pub amount : f64,
+    pub unit : String
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecipeInfo {
+    pub food_id : i32,
+    pub name : String,
+    pub serving_id : i32, /* yield serving, usable like any other serving (e.g in meals) */
+    pub yield_amount : f64,
+    pub yield_unit : String,
+    pub ingredients : Vec<RecipeInfoIngredient>
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecipeResponse {
+    pub recipe : RecipeInfo
+}
+
+#[get("/api/recipe/{food_id}")]
+pub async fn api_recipe(user : Option<AuthenticatedUser>, food_id : web::Path<i32>, dbpool : web::Data<PgPool>) -> Result<web::Json<ApiResponse<RecipeResponse>>, ApiError> {
+    let food_id = food_id.into_inner();
+    let recipe = fetch_recipe_info(food_id, user.map(|user| user.id), &dbpool).await.ok_or(ApiError::NotFound)?;
+
+    Ok(web::Json(ApiResponse::ok(RecipeResponse { recipe })))
+}
@@ -1,10 +1,11 @@
-use actix_web::{post, Responder, web};
+use actix_web::{post, web};
 use serde::{Deserialize};
 use log::info;
 use chrono::NaiveDate;
 use sqlx::PgPool;
 use crate::{
     models::{ApiResponse, ApiError},
+    settings::Settings,
     utils::{
         database::create_user_account,
         validation::*
@@ -22,24 +23,14 @@ pub struct RegisterForm {
 }
 
 #[post("/api/register")]
-pub async fn api_register(form : web::Form<RegisterForm>, dbpool : web::Data<PgPool>) -> impl Responder {
+pub async fn api_register(form : web::Form<RegisterForm>, settings : web::Data<Settings>, dbpool : web::Data<PgPool>) -> Result<web::Json<ApiResponse<&'static str>>, ApiError> {
     info!("{:?}", form);
 
-    let validate_user_input = || {
-        check_name(&form.name) &&
-            check_birthdate(&form.birthdate) &&
-            check_email(&form.email) &&
-            check_password(&form.password) &&
-            check_gender(form.gender) &&
-            check_weight(form.weight)
-    };
+    FieldChecks::from(check_user_profile(&form.name, &form.birthdate, &form.email, form.gender, form.weight))
+        .check(check_password(&form.password), "password", "must be at most 255 characters")
+        .finish()?;
 
-    if !validate_user_input() {
-        return web::Json(ApiResponse::err(ApiError::InvalidInput));
-    }
+    create_user_account(&form, &settings.password_hash, &dbpool).await.map_err(|_| ApiError::RegistrationFailed)?;
 
-    match create_user_account(&form, &dbpool).await {
-        Ok(_) => web::Json(ApiResponse::ok("OK")),
-        Err(_) => web::Json(ApiResponse::err(ApiError::RegistrationFailed))
-    }
+    Ok(web::Json(ApiResponse::ok("OK")))
 }
@@ -0,0 +1,40 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError, FieldError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_meal_servings, reorder_meal_servings},
+        validation::{parse_id_list, check_order}
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ReorderMealServingsForm {
+    meal_id : i32,
+    meal_serving_ids : String /* every serving of the meal, in the new order (e.g '7,5,6') */
+}
+
+#[post("/api/reorder_meal_servings")]
+pub async fn api_reorder_meal_servings(user : AuthenticatedUser, form : web::Form<ReorderMealServingsForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Meal(form.meal_id), &dbpool).await?;
+
+    let meal_servings = match fetch_meal_servings(form.meal_id, &dbpool).await {
+        Some(meal_servings) => meal_servings,
+        None => return Err(ApiError::ReorderMealServings)
+    };
+
+    let meal_serving_ids : Vec<i32> = meal_servings.iter().map(|meal_serving| meal_serving.id).collect();
+    let order = match parse_id_list(&form.meal_serving_ids) {
+        Some(order) if check_order(&order, &meal_serving_ids) => order,
+        _ => return Err(ApiError::InvalidFields(vec![FieldError::new("meal_serving_ids", "must list every serving of the meal once")]))
+    };
+
+    match reorder_meal_servings(form.meal_id, &order, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::ReorderMealServings)
+    }
+}
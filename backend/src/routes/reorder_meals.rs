@@ -0,0 +1,40 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use crate::{
+    models::{ApiResponse, ApiError, FieldError},
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::{fetch_diet_meals, reorder_meals},
+        validation::{parse_id_list, check_order}
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ReorderMealsForm {
+    diet_id : i32,
+    meal_ids : String /* every meal of the diet, in the new order (e.g '3,1,2') */
+}
+
+#[post("/api/reorder_meals")]
+pub async fn api_reorder_meals(user : AuthenticatedUser, form : web::Form<ReorderMealsForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    user.check_owner(Resource::Diet(form.diet_id), &dbpool).await?;
+
+    let meals = match fetch_diet_meals(form.diet_id, &dbpool).await {
+        Some(meals) => meals,
+        None => return Err(ApiError::ReorderMeals)
+    };
+
+    let meal_ids : Vec<i32> = meals.iter().map(|meal| meal.id).collect();
+    let order = match parse_id_list(&form.meal_ids) {
+        Some(order) if check_order(&order, &meal_ids) => order,
+        _ => return Err(ApiError::InvalidFields(vec![FieldError::new("meal_ids", "must list every meal of the diet once")]))
+    };
+
+    match reorder_meals(form.diet_id, &order, &dbpool).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Err(_) => Err(ApiError::ReorderMeals)
+    }
+}
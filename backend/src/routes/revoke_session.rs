@@ -0,0 +1,27 @@
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use log::info;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::revoke_user_session,
+    }
+};
+
+#[derive(Deserialize, Debug)]
+pub struct RevokeSessionForm {
+    session_id : Uuid /* public ID, as listed by '/api/sessions' */
+}
+
+#[post("/api/revoke_session")]
+pub async fn api_revoke_session(user : AuthenticatedUser, form : web::Form<RevokeSessionForm>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    info!("{:?}", form);
+    match revoke_user_session(user.id, &form.session_id, &dbpool).await {
+        Ok(true) => Ok(HttpResponse::Ok().json(ApiResponse::ok("OK"))),
+        Ok(false) => Err(ApiError::NotFound),
+        Err(_) => Err(ApiError::RevokeSession)
+    }
+}
@@ -0,0 +1,49 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_user_sessions
+    }
+};
+
+#[derive(Serialize, Debug)]
+pub struct SessionInfo {
+    pub id : Uuid,
+    pub user_agent : Option<String>,
+    pub created_at : DateTime<Utc>,
+    pub last_seen : DateTime<Utc>,
+    pub expiry_date : DateTime<Utc>,
+    pub current : bool
+}
+
+#[derive(Serialize, Debug)]
+struct SessionsResponse {
+    sessions : Vec<SessionInfo>
+}
+
+#[get("/api/sessions")]
+pub async fn api_sessions(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let sessions = match fetch_user_sessions(user.id, &dbpool).await {
+        Some(sessions) => sessions,
+        None => return Err(ApiError::QuerySessions)
+    };
+
+    let sessions_info : Vec<SessionInfo> = sessions
+        .into_iter()
+        .map(|s| SessionInfo {
+            id: s.public_id,
+            user_agent: s.user_agent,
+            created_at: s.created_at,
+            last_seen: s.last_seen,
+            expiry_date: s.expiry_date,
+            current: s.id == user.session_id
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(SessionsResponse { sessions: sessions_info })))
+}
@@ -1,42 +1,42 @@
-use actix_web::{get, Responder, web, HttpRequest};
+use actix_web::{get, web, HttpResponse};
 use serde::Serialize;
 use sqlx::PgPool;
 use chrono::NaiveDate;
 use crate::{
-    models::{ApiResponse, ApiError},
+    models::{ApiResponse, ApiError, UserAccount},
     utils::{
+        auth::AuthenticatedUser,
         database::fetch_user_account,
-        request::get_user_id
     }
 };
 
 #[derive(Serialize, Debug)]
-struct UserResponse {
-    name : String,
-    birthdate : NaiveDate,
-    gender : String,
-    weight : f64
+pub struct UserResponse {
+    pub name : String,
+    pub email : String,
+    pub birthdate : NaiveDate,
+    pub gender : String,
+    pub weight : f64
 }
 
-#[get("/api/user")]
-pub async fn api_user(req : HttpRequest, dbpool : web::Data<PgPool>) -> impl Responder {
-    let mut resp = web::Json(ApiResponse::<UserResponse>::err(ApiError::NotLoggedIn)).respond_to(&req);
-    let user_id = match get_user_id(&req, &mut resp, &dbpool).await {
-        Some(id) => id,
-        None => return resp
-    };
+impl From<UserAccount> for UserResponse {
+    fn from(user_account : UserAccount) -> Self {
+        Self {
+            name: user_account.name,
+            email: user_account.email,
+            birthdate: user_account.birthdate,
+            gender: user_account.gender,
+            weight: user_account.weight
+        }
+    }
+}
 
-    let user_account = match fetch_user_account(user_id, &dbpool).await {
+#[get("/api/user")]
+pub async fn api_user(user : AuthenticatedUser, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let user_account = match fetch_user_account(user.id, &dbpool).await {
         Some(user) => user,
-        None => return web::Json(ApiResponse::<UserResponse>::err(ApiError::QueryDiets)).respond_to(&req)
-    };
-
-    let user_resp = UserResponse {
-        name: user_account.name.clone(),
-        birthdate: user_account.birthdate,
-        gender: user_account.gender,
-        weight: user_account.weight
+        None => return Err(ApiError::QueryUser)
     };
 
-    web::Json(ApiResponse::ok(user_resp)).respond_to(&req)
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(UserResponse::from(user_account))))
 }
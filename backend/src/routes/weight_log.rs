@@ -0,0 +1,31 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use crate::{
+    models::{ApiResponse, ApiError, WeightEntry},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_weight_entries,
+        validation::FieldChecks
+    }
+};
+
+#[derive(Serialize, Debug)]
+struct WeightLogResponse {
+    entries : Vec<WeightEntry>
+}
+
+#[get("/api/weight_log/{from_date}/{to_date}")]
+pub async fn api_weight_log(user : AuthenticatedUser, path : web::Path<(NaiveDate, NaiveDate)>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let (from_date, to_date) = path.into_inner();
+
+    FieldChecks::new()
+        .check(from_date <= to_date, "to_date", "must not be before from_date")
+        .finish()?;
+
+    match fetch_weight_entries(user.id, from_date, to_date, &dbpool).await {
+        Some(entries) => Ok(HttpResponse::Ok().json(ApiResponse::ok(WeightLogResponse { entries }))),
+        None => Err(ApiError::QueryWeightLog)
+    }
+}
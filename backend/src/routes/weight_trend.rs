@@ -0,0 +1,61 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use chrono::{Duration, NaiveDate};
+use crate::{
+    models::{ApiResponse, ApiError},
+    utils::{
+        auth::AuthenticatedUser,
+        database::fetch_weight_entries,
+        weight::{moving_averages, weekly_rate},
+        validation::FieldChecks
+    }
+};
+
+/// Amount of days averaged for each point of the trend
+const MOVING_AVERAGE_DAYS : i64 = 7;
+
+#[derive(Serialize, Debug)]
+struct WeightTrendPoint {
+    entry_date : NaiveDate,
+    weight : f64,
+    moving_average : f64
+}
+
+#[derive(Serialize, Debug)]
+struct WeightTrendResponse {
+    points : Vec<WeightTrendPoint>,
+    weekly_rate : Option<f64> /* kilograms per week, null if there is not enough data */
+}
+
+#[get("/api/weight_trend/{from_date}/{to_date}")]
+pub async fn api_weight_trend(user : AuthenticatedUser, path : web::Path<(NaiveDate, NaiveDate)>, dbpool : web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let (from_date, to_date) = path.into_inner();
+
+    FieldChecks::new()
+        .check(from_date <= to_date, "to_date", "must not be before from_date")
+        .finish()?;
+
+    // Also fetch the days before the range, so that the first points have a full moving average window
+    let window_start = from_date.checked_sub_signed(Duration::days(MOVING_AVERAGE_DAYS - 1)).ok_or(ApiError::InvalidInput)?;
+    let entries = match fetch_weight_entries(user.id, window_start, to_date, &dbpool).await {
+        Some(entries) => entries,
+        None => return Err(ApiError::QueryWeightLog)
+    };
+
+    let averages = moving_averages(&entries, MOVING_AVERAGE_DAYS);
+    let first_index = entries.iter().position(|e| e.entry_date >= from_date).unwrap_or(entries.len());
+    let in_range = &entries[first_index..];
+
+    let points = in_range
+        .iter()
+        .zip(&averages[first_index..])
+        .map(|(entry, average)| WeightTrendPoint {
+            entry_date: entry.entry_date,
+            weight: entry.weight,
+            moving_average: *average
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(WeightTrendResponse { points, weekly_rate: weekly_rate(in_range) })))
+}
@@ -1,11 +1,58 @@
 use serde::{Deserialize};
 use config::{Config, File, ConfigError};
 
-#[derive(Deserialize, Debug)]
+/// Argon2id cost settings used for password hashing
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PasswordHashSettings {
+    pub memory_cost : u32, /* in KiB */
+    pub time_cost : u32, /* iterations */
+    pub parallelism : u32
+}
+
+impl Default for PasswordHashSettings {
+    fn default() -> Self {
+        Self {
+            memory_cost: 19456,
+            time_cost: 2,
+            parallelism: 1
+        }
+    }
+}
+
+/// Sessions expire after 'duration_hours' without use (sliding renewal on every
+/// request), and never last longer than 'max_duration_hours' after login
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SessionSettings {
+    pub duration_hours : i32,
+    pub max_duration_hours : i32,
+    pub cookie_http_only : bool,
+    pub cookie_secure : bool, /* enable when serving over HTTPS */
+    pub cookie_same_site : String /* "Strict", "Lax" or "None" (which always makes the cookies Secure) */
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            duration_hours: 720,
+            max_duration_hours: 8760,
+            cookie_http_only: true,
+            cookie_secure: false,
+            cookie_same_site: "Lax".to_string()
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct Settings {
     pub host : String,
     pub port : u16,
-    pub database_url : String
+    pub database_url : String,
+    #[serde(default)]
+    pub password_hash : PasswordHashSettings,
+    #[serde(default)]
+    pub session : SessionSettings
 }
 
 impl Settings {
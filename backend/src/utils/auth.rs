@@ -0,0 +1,101 @@
+use std::{future::Future, pin::Pin};
+use actix_web::{dev::Payload, error::{ErrorInternalServerError, InternalError}, web, FromRequest, HttpMessage, HttpRequest, ResponseError};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    models::ApiError,
+    utils::{
+        database::*,
+        request::{add_session_removal_cookies, get_session_id, session_settings, SessionRenewal}
+    }
+};
+
+/// User of a valid session. Routes that take it as an argument answer 401 (and clear the session
+/// cookies) to anonymous users, and routes that take an 'Option<AuthenticatedUser>' are public.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser {
+    pub id : i32,
+    pub session_id : Uuid
+}
+
+/// Resources that belong to a user, by their ID
+#[derive(Debug, Clone, Copy)]
+pub enum Resource {
+    Diet(i32),
+    Meal(i32),
+    MealServing(i32),
+    Food(i32),
+    Serving(i32),
+    RecipeIngredient(i32),
+    DiaryEntry(i32),
+    WeightEntry(i32)
+}
+
+impl Resource {
+    async fn user_id(self, dbpool : &PgPool) -> Option<i32> {
+        match self {
+            Resource::Diet(id) => get_diet_user_id(id, dbpool).await,
+            Resource::Meal(id) => get_meal_user_id(id, dbpool).await,
+            Resource::MealServing(id) => get_meal_serving_user_id(id, dbpool).await,
+            Resource::Food(id) => get_food_user_id(id, dbpool).await,
+            Resource::Serving(id) => get_serving_user_id(id, dbpool).await,
+            Resource::RecipeIngredient(id) => {
+                let ingredient = fetch_recipe_ingredient(id, dbpool).await?;
+                get_food_user_id(ingredient.recipe_id, dbpool).await
+            },
+            Resource::DiaryEntry(id) => fetch_diary_entry(id, dbpool).await.map(|entry| entry.user_id),
+            Resource::WeightEntry(id) => fetch_weight_entry(id, dbpool).await.map(|entry| entry.user_id)
+        }
+    }
+}
+
+impl AuthenticatedUser {
+    async fn authenticate(req : &HttpRequest) -> Result<Self, actix_web::Error> {
+        let mut resp = ApiError::NotLoggedIn.error_response();
+        let session_id = match get_session_id(req, &mut resp) {
+            Some(id) => id,
+            None => return Err(InternalError::from_response(ApiError::NotLoggedIn, resp).into())
+        };
+
+        let dbpool = req.app_data::<web::Data<PgPool>>().ok_or_else(|| ErrorInternalServerError("Missing database pool"))?;
+        match get_session_user_id(&session_id, &session_settings(req), dbpool).await {
+            Some((id, expiry_date)) => {
+                req.extensions_mut().insert(SessionRenewal { session_id, expiry_date });
+                Ok(Self { id, session_id })
+            },
+            None => {
+                add_session_removal_cookies(&mut resp);
+                Err(InternalError::from_response(ApiError::NotLoggedIn, resp).into())
+            }
+        }
+    }
+
+    /// Checks that the user owns a resource (404 if it doesn't exist, 403 if it belongs to someone else)
+    pub async fn check_owner(&self, resource : Resource, dbpool : &PgPool) -> Result<(), ApiError> {
+        match resource.user_id(dbpool).await {
+            Some(id) if id == self.id => Ok(()),
+            Some(_) => Err(ApiError::AccessDenied),
+            None => Err(ApiError::NotFound)
+        }
+    }
+
+    /// Same as 'check_owner', but resources of the public catalog (e.g foods and their servings) are also allowed
+    /// (routes that take a serving to put in a meal, the diary or a recipe check it with this, see 'tests/auth.rs')
+    pub async fn check_access(&self, resource : Resource, dbpool : &PgPool) -> Result<(), ApiError> {
+        match resource.user_id(dbpool).await {
+            Some(id) if id == self.id || id == CATALOG_USER_ID => Ok(()),
+            Some(_) => Err(ApiError::AccessDenied),
+            None => Err(ApiError::NotFound)
+        }
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req : &HttpRequest, _payload : &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { Self::authenticate(&req).await })
+    }
+}
@@ -0,0 +1,35 @@
+/// Length of a normalized barcode (GTIN-14)
+pub const BARCODE_LENGTH : usize = 14;
+
+/// Calculates the check digit of a GTIN, given all of its other digits
+fn gtin_check_digit(digits : &[u32]) -> u32 {
+    // From the right, the digits are weighted 3, 1, 3, 1, ...
+    let sum : u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| if i % 2 == 0 { digit * 3 } else { *digit })
+        .sum();
+
+    (10 - sum % 10) % 10
+}
+
+/// Normalizes a UPC-A, EAN-13, EAN-8 or GTIN-14 barcode into 14 digits (zero padded on the left),
+/// so that the same product always has the same code. Returns 'None' if the barcode is invalid
+/// (wrong length, not numeric, or wrong check digit).
+pub fn normalize_barcode(code : &str) -> Option<String> {
+    // Ignore the separators that are usually printed with the code (e.g '0 12345 67890 5')
+    let code : String = code.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+
+    if ![8, 12, 13, 14].contains(&code.len()) {
+        return None;
+    }
+
+    let digits : Vec<u32> = code.chars().map(|c| c.to_digit(10)).collect::<Option<Vec<u32>>>()?;
+    let (check_digit, payload) = digits.split_last()?;
+    if gtin_check_digit(payload) != *check_digit {
+        return None;
+    }
+
+    Some(format!("{:0>width$}", code, width = BARCODE_LENGTH))
+}
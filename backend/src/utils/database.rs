@@ -1,72 +1,301 @@
 use crate::{
     models::*,
     routes::{
+        diary::DiaryEntryInfo,
         diet_nutrition::DietInfoNutrient,
-        food_search::{SearchFood, SearchFoodServing},
+        edit_user::EditUserForm,
+        food_search::{FoodOwner, FoodSearchFilters, FoodSource, NutrientBasis, SearchFood, SearchFoodServing, SortOrder},
         login::LoginForm,
         meals::{MealInfoFood, MealInfoNutrient},
+        recipe::{RecipeInfo, RecipeInfoIngredient},
         register::RegisterForm,
     },
-    utils::{hash::sha256str, time::calculate_age},
+    settings::{PasswordHashSettings, SessionSettings, Settings},
+    utils::{
+        hash::{hash_password_blocking, verify_dummy_password, verify_password_blocking, PasswordMatch},
+        time::calculate_age,
+        units::ServingUnit,
+        usda::{CatalogFood, ImportSummary, USDA_BASE_SERVING},
+    },
 };
 use anyhow::{Error, Result};
-use chrono::{Datelike, Utc};
-use sqlx::{PgPool, Row};
+use log::error;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row, Transaction};
 use uuid::Uuid;
 
-pub async fn create_user_account(data: &RegisterForm, dbpool: &PgPool) -> Result<(), sqlx::Error> {
-    let password_hash = sha256str(data.password.as_str());
+/// Owner of the public food catalog (foods imported from USDA)
+pub const CATALOG_USER_ID: i32 = 1;
+
+/// Checks if a query failed on a UNIQUE constraint (e.g an email that is already used), which is a
+/// mistake of the request rather than a failure of the database
+pub fn is_unique_violation(error: &Error) -> bool {
+    database_error_code(error).as_deref() == Some("23505")
+}
+
+/// Same as 'is_unique_violation', for a reference to a row that doesn't exist
+pub fn is_foreign_key_violation(error: &Error) -> bool {
+    database_error_code(error).as_deref() == Some("23503")
+}
+
+fn database_error_code(error: &Error) -> Option<String> {
+    let code = error.downcast_ref::<sqlx::Error>()?.as_database_error()?.code()?;
+    Some(code.into_owned())
+}
+
+pub async fn create_user_account(
+    data: &RegisterForm,
+    hash_settings: &PasswordHashSettings,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let password_hash = hash_password_blocking(data.password.as_str(), hash_settings).await?;
 
-    sqlx::query("INSERT INTO user_account(name, email, gender, weight, birthdate, password_hash) VALUES ($1, $2, $3, $4, $5, $6)")
+    let mut tx = dbpool.begin().await?;
+
+    let query_result = sqlx::query("INSERT INTO user_account(name, email, gender, weight, birthdate, password_hash) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id")
         .bind(&data.name)
         .bind(&data.email)
         .bind(data.gender.to_string())
         .bind(data.weight)
         .bind(data.birthdate)
         .bind(&password_hash)
-        .execute(dbpool)
+        .fetch_one(&mut tx)
+        .await?;
+
+    let user_id: i32 = query_result.try_get("id")?;
+
+    // Start the weight history with the weight given at registration
+    sqlx::query("INSERT INTO weight_entry(user_id, entry_date, weight) VALUES ($1, CURRENT_DATE, $2)")
+        .bind(user_id)
+        .bind(data.weight)
+        .execute(&mut tx)
         .await?;
 
+    tx.commit().await?;
+
     Ok(())
 }
 
-pub async fn authenticate_user(data: &LoginForm, dbpool: &PgPool) -> Result<String> {
-    let query_result = sqlx::query("SELECT id, password_hash FROM user_account WHERE email = $1")
+pub async fn authenticate_user(
+    data: &LoginForm,
+    user_agent: Option<&str>,
+    settings: &Settings,
+    dbpool: &PgPool,
+) -> Result<Uuid> {
+    // Nobody logs in as the catalog account (its initial password is public)
+    let query_result = sqlx::query("SELECT id FROM user_account WHERE email = $1 AND id <> $2")
         .bind(&data.email)
+        .bind(CATALOG_USER_ID)
+        .fetch_optional(dbpool)
+        .await?;
+
+    let user_id: i32 = match query_result {
+        Some(row) => row.try_get("id")?,
+        None => {
+            verify_dummy_password(&data.password, &settings.password_hash).await;
+            return Err(Error::msg("Unknown email"));
+        }
+    };
+
+    verify_user_password(user_id, &data.password, &settings.password_hash, dbpool).await?;
+
+    create_session(user_id, user_agent, &settings.session, dbpool).await
+}
+
+/// Fails if 'password' is not the password of the user
+pub async fn verify_user_password(
+    user_id: i32,
+    password: &str,
+    hash_settings: &PasswordHashSettings,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let query_result = sqlx::query("SELECT password_hash FROM user_account WHERE id = $1")
+        .bind(user_id)
         .fetch_one(dbpool)
         .await?;
 
-    let user_id: i32 = query_result.try_get("id")?;
     let password_hash: String = query_result.try_get("password_hash")?;
 
-    let attempt_hash = sha256str(data.password.as_str());
-
-    if attempt_hash != password_hash {
-        return Err(Error::msg("Passwords don't match"));
+    match verify_password_blocking(password, &password_hash, hash_settings).await {
+        PasswordMatch::Valid => {}
+        PasswordMatch::NeedsRehash => {
+            // Upgrade legacy (or outdated) hashes transparently, now that the password is known
+            let new_hash = hash_password_blocking(password, hash_settings).await?;
+            sqlx::query("UPDATE user_account SET password_hash = $1 WHERE id = $2")
+                .bind(&new_hash)
+                .bind(user_id)
+                .execute(dbpool)
+                .await?;
+        }
+        PasswordMatch::Invalid => return Err(Error::msg("Passwords don't match")),
     }
 
+    Ok(())
+}
+
+pub async fn update_user_account(user_id: i32, data: &EditUserForm, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    // A new weight is also logged in the weight history
+    sqlx::query("INSERT INTO weight_entry(user_id, entry_date, weight) SELECT id, CURRENT_DATE, $2 FROM user_account WHERE id = $1 AND weight <> $2 ON CONFLICT (user_id, entry_date) DO UPDATE SET weight = EXCLUDED.weight")
+        .bind(user_id)
+        .bind(data.weight)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("UPDATE user_account SET name = $1, email = $2, gender = $3, weight = $4, birthdate = $5 WHERE id = $6")
+        .bind(&data.name)
+        .bind(&data.email)
+        .bind(data.gender.to_string())
+        .bind(data.weight)
+        .bind(data.birthdate)
+        .bind(user_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Replaces the password of the user, and revokes every session except 'current_session_id'
+pub async fn change_user_password(
+    user_id: i32,
+    new_password: &str,
+    current_session_id: &Uuid,
+    hash_settings: &PasswordHashSettings,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let password_hash = hash_password_blocking(new_password, hash_settings).await?;
+
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("UPDATE user_account SET password_hash = $1 WHERE id = $2")
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM user_session WHERE user_id = $1 AND id <> $2")
+        .bind(user_id)
+        .bind(current_session_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn create_session(
+    user_id: i32,
+    user_agent: Option<&str>,
+    session_settings: &SessionSettings,
+    dbpool: &PgPool,
+) -> Result<Uuid> {
     let session_id = Uuid::new_v4();
-    let expiry_date = Utc::now();
-    let expiry_date = expiry_date.with_year(expiry_date.year() + 1);
 
-    sqlx::query("INSERT INTO user_session(id, user_id, expiry_date) VALUES ($1, $2, $3)")
+    sqlx::query("INSERT INTO user_session(id, user_id, user_agent, expiry_date) VALUES ($1, $2, $3, NOW() + make_interval(hours => LEAST($4, $5)))")
         .bind(session_id)
         .bind(user_id)
-        .bind(expiry_date)
+        .bind(user_agent)
+        .bind(session_settings.duration_hours)
+        .bind(session_settings.max_duration_hours)
         .execute(dbpool)
         .await?;
 
-    Ok(session_id.to_string())
+    Ok(session_id)
 }
 
-pub async fn get_session_user_id(session_id: &Uuid, dbpool: &PgPool) -> Option<i32> {
-    let query_result = sqlx::query("SELECT user_id FROM user_session WHERE id = $1")
+/// Returns the user of a session that has not expired yet, and renews its expiry date (also returned)
+pub async fn get_session_user_id(
+    session_id: &Uuid,
+    session_settings: &SessionSettings,
+    dbpool: &PgPool,
+) -> Option<(i32, DateTime<Utc>)> {
+    let query_result = sqlx::query("UPDATE user_session SET last_seen = NOW(), expiry_date = LEAST(NOW() + make_interval(hours => $2), created_at + make_interval(hours => $3)) WHERE id = $1 AND expiry_date > NOW() RETURNING user_id, expiry_date")
         .bind(session_id)
+        .bind(session_settings.duration_hours)
+        .bind(session_settings.max_duration_hours)
         .fetch_one(dbpool)
         .await
         .ok()?;
 
-    query_result.try_get::<i32, _>("user_id").ok()
+    let user_id = query_result.try_get::<i32, _>("user_id").ok()?;
+    let expiry_date = query_result.try_get::<DateTime<Utc>, _>("expiry_date").ok()?;
+    Some((user_id, expiry_date))
+}
+
+pub async fn fetch_user_sessions(user_id: i32, dbpool: &PgPool) -> Option<Vec<UserSession>> {
+    let sessions = sqlx::query_as::<_, UserSession>("SELECT * FROM user_session WHERE user_id = $1 AND expiry_date > NOW() ORDER BY last_seen DESC")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(sessions)
+}
+
+/// Returns false if the user has no such session
+pub async fn revoke_user_session(user_id: i32, public_id: &Uuid, dbpool: &PgPool) -> Result<bool> {
+    let query_result = sqlx::query("DELETE FROM user_session WHERE user_id = $1 AND public_id = $2")
+        .bind(user_id)
+        .bind(public_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(query_result.rows_affected() > 0)
+}
+
+pub async fn delete_user_sessions(user_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM user_session WHERE user_id = $1")
+        .bind(user_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes a user account and everything tied to it
+pub async fn delete_user_account(user_id: i32, dbpool: &PgPool) -> Result<()> {
+    // The catalog foods belong to this account, and everyone's diets and diaries use them
+    if user_id == CATALOG_USER_ID {
+        return Err(Error::msg("The catalog user cannot be deleted"));
+    }
+
+    let mut tx = dbpool.begin().await?;
+
+    // Delete in foreign key order: whatever references the diets and the
+    // user's foods goes before them, and the account goes last
+    let queries = [
+        "DELETE FROM meal_serving WHERE meal_id IN (SELECT meal.id FROM meal JOIN diet ON diet.id = meal.diet_id WHERE diet.user_id = $1)",
+        "DELETE FROM meal_serving WHERE serving_id IN (SELECT serving.id FROM serving JOIN food ON food.id = serving.food_id WHERE food.user_id = $1)",
+        "DELETE FROM meal WHERE diet_id IN (SELECT id FROM diet WHERE user_id = $1)",
+        "DELETE FROM diet_nutrition WHERE diet_id IN (SELECT id FROM diet WHERE user_id = $1)",
+        "DELETE FROM diet WHERE user_id = $1",
+        "DELETE FROM diary_entry WHERE user_id = $1 OR serving_id IN (SELECT serving.id FROM serving JOIN food ON food.id = serving.food_id WHERE food.user_id = $1)",
+        "DELETE FROM serving_usage WHERE user_id = $1 OR serving_id IN (SELECT serving.id FROM serving JOIN food ON food.id = serving.food_id WHERE food.user_id = $1)",
+        "DELETE FROM food_favorite WHERE user_id = $1 OR food_id IN (SELECT id FROM food WHERE user_id = $1)",
+        "DELETE FROM recipe_ingredient WHERE recipe_id IN (SELECT id FROM food WHERE user_id = $1) OR serving_id IN (SELECT serving.id FROM serving JOIN food ON food.id = serving.food_id WHERE food.user_id = $1)",
+        "DELETE FROM recipe WHERE food_id IN (SELECT id FROM food WHERE user_id = $1)",
+        "DELETE FROM serving_nutrient WHERE serving_id IN (SELECT serving.id FROM serving JOIN food ON food.id = serving.food_id WHERE food.user_id = $1)",
+        "DELETE FROM serving WHERE relative IS NOT NULL AND food_id IN (SELECT id FROM food WHERE user_id = $1)",
+        "DELETE FROM serving WHERE food_id IN (SELECT id FROM food WHERE user_id = $1)",
+        "DELETE FROM food_barcode WHERE food_id IN (SELECT id FROM food WHERE user_id = $1)",
+        "DELETE FROM food WHERE user_id = $1",
+        "DELETE FROM weight_entry WHERE user_id = $1",
+        "DELETE FROM user_session WHERE user_id = $1",
+        "DELETE FROM user_account WHERE id = $1",
+    ];
+
+    for query in queries {
+        sqlx::query(query).bind(user_id).execute(&mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
 }
 
 pub async fn fetch_user_diets(user_id: i32, dbpool: &PgPool) -> Result<Vec<Diet>> {
@@ -113,7 +342,7 @@ pub async fn fetch_diet_info_nutrition(
     diet_id: i32,
     dbpool: &PgPool,
 ) -> Option<Vec<DietInfoNutrient>> {
-    let diet_info_nutrients = sqlx::query_as::<_, DietInfoNutrient>("SELECT nutrient.name AS name, diet_nutrition.min_intake AS min_amount, diet_nutrition.max_intake AS max_amount, nutrient.unit AS unit, diet_nutrition.relative AS relative FROM diet_nutrition JOIN nutrient ON nutrient.id = diet_nutrition.nutrient_id WHERE diet_nutrition.diet_id = $1")
+    let diet_info_nutrients = sqlx::query_as::<_, DietInfoNutrient>("SELECT nutrient.id AS nutrient_id, nutrient.name AS name, diet_nutrition.min_intake AS min_amount, diet_nutrition.max_intake AS max_amount, nutrient.unit AS unit, diet_nutrition.relative AS relative FROM diet_nutrition JOIN nutrient ON nutrient.id = diet_nutrition.nutrient_id WHERE diet_nutrition.diet_id = $1")
         .bind(diet_id)
         .fetch_all(dbpool)
         .await
@@ -122,6 +351,43 @@ pub async fn fetch_diet_info_nutrition(
     Some(diet_info_nutrients)
 }
 
+pub async fn add_diet_nutrient(diet_nutrient: &DietNutrient, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("INSERT INTO diet_nutrition(diet_id, nutrient_id, min_intake, max_intake, relative) VALUES ($1, $2, $3, $4, $5)")
+        .bind(diet_nutrient.diet_id)
+        .bind(diet_nutrient.nutrient_id)
+        .bind(diet_nutrient.min_intake)
+        .bind(diet_nutrient.max_intake)
+        .bind(diet_nutrient.relative)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns false if the nutrient is not in the diet
+pub async fn update_diet_nutrient(diet_nutrient: &DietNutrient, dbpool: &PgPool) -> Result<bool> {
+    let query_result = sqlx::query("UPDATE diet_nutrition SET min_intake = $1, max_intake = $2, relative = $3 WHERE diet_id = $4 AND nutrient_id = $5")
+        .bind(diet_nutrient.min_intake)
+        .bind(diet_nutrient.max_intake)
+        .bind(diet_nutrient.relative)
+        .bind(diet_nutrient.diet_id)
+        .bind(diet_nutrient.nutrient_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(query_result.rows_affected() > 0)
+}
+
+pub async fn delete_diet_nutrient(diet_id: i32, nutrient_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM diet_nutrition WHERE diet_id = $1 AND nutrient_id = $2")
+        .bind(diet_id)
+        .bind(nutrient_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn fetch_nutrients(dbpool: &PgPool) -> Option<Vec<Nutrient>> {
     let nutrients = sqlx::query_as::<_, Nutrient>("SELECT * FROM nutrient")
         .fetch_all(dbpool)
@@ -131,8 +397,18 @@ pub async fn fetch_nutrients(dbpool: &PgPool) -> Option<Vec<Nutrient>> {
     Some(nutrients)
 }
 
+pub async fn fetch_nutrient(nutrient_id: i32, dbpool: &PgPool) -> Option<Nutrient> {
+    let nutrient = sqlx::query_as::<_, Nutrient>("SELECT * FROM nutrient WHERE id = $1")
+        .bind(nutrient_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(nutrient)
+}
+
 pub async fn fetch_diet_meals(diet_id: i32, dbpool: &PgPool) -> Option<Vec<Meal>> {
-    let meals = sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE diet_id = $1")
+    let meals = sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE diet_id = $1 ORDER BY position, id")
         .bind(diet_id)
         .fetch_all(dbpool)
         .await
@@ -141,85 +417,96 @@ pub async fn fetch_diet_meals(diet_id: i32, dbpool: &PgPool) -> Option<Vec<Meal>
     Some(meals)
 }
 
-/* TODO: Clean up this function (taken from old codebase, needs refactoring) */
-pub async fn fetch_meal_info_foods(meal_id: i32, dbpool: &PgPool) -> Option<Vec<MealInfoFood>> {
-    let query_foods = async {
-        sqlx::query("SELECT food.id AS id, food.name AS name, meal_serving.id AS meal_serving_id, serving.id AS serving_id, serving.amount AS serving_base, meal_serving.amount AS amount, serving.unit AS unit, serving.relative AS relative FROM meal_serving JOIN serving ON meal_serving.serving_id = serving.id JOIN food ON serving.food_id = food.id WHERE meal_serving.meal_id = $1")
-			.bind(meal_id)
-			.fetch_all(dbpool)
-			.await
-    };
+/// Fetches the nutrients of a serving for one 'serving_base'. Relative servings take the
+/// nutrients of the serving they are relative to, and always have a base of 1.
+async fn fetch_serving_base_nutrients(
+    serving_id: i32,
+    serving_base: f64,
+    serving_relative: Option<i32>,
+    dbpool: &PgPool,
+) -> Option<(f64, Vec<MealInfoNutrient>)> {
+    let mut serving_base = serving_base;
+    let mut serving_rel_amount: f64 = 0.0;
+
+    if serving_relative.is_some() {
+        serving_base = 1.0;
+
+        let row = sqlx::query("SELECT amount FROM serving WHERE serving.id = $1")
+            .bind(serving_id)
+            .fetch_one(dbpool)
+            .await
+            .ok()?;
+
+        serving_rel_amount = row.try_get("amount").ok()?;
+    }
 
-    let foods = match query_foods.await {
-        Ok(r) => r,
-        Err(_) => return None,
+    let query_nutrients = async {
+        if let Some(id) = serving_relative {
+            sqlx::query("SELECT nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit, serving.amount AS serving_base_amount FROM serving_nutrient JOIN serving ON serving.id = serving_nutrient.serving_id JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving.id = $1")
+                .bind(id)
+                .fetch_all(dbpool)
+                .await
+        } else {
+            sqlx::query("SELECT nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit FROM serving_nutrient JOIN serving ON serving.id = serving_nutrient.serving_id JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving.id = $1")
+                .bind(serving_id)
+                .fetch_all(dbpool)
+                .await
+        }
     };
 
+    let nutrients = query_nutrients.await.ok()?;
+
+    let mut base_nutrients: Vec<MealInfoNutrient> = vec![];
+    for nutrient in nutrients {
+        let nutrient_name: String = nutrient.try_get("name").ok()?;
+        let mut nutrient_amount: f64 = nutrient.try_get("amount").ok()?;
+        let nutrient_unit: String = nutrient.try_get("unit").ok()?;
+
+        if serving_relative.is_some() {
+            let serving_base_amount: f64 = nutrient.try_get("serving_base_amount").ok()?;
+            nutrient_amount *= serving_rel_amount / serving_base_amount;
+        }
+
+        base_nutrients.push(MealInfoNutrient {
+            name: nutrient_name,
+            amount: nutrient_amount,
+            unit: nutrient_unit,
+        });
+    }
+
+    Some((serving_base, base_nutrients))
+}
+
+pub async fn fetch_meal_info_foods(meal_id: i32, dbpool: &PgPool) -> Option<Vec<MealInfoFood>> {
+    let foods = sqlx::query("SELECT food.id AS id, food.name AS name, meal_serving.id AS meal_serving_id, serving.id AS serving_id, serving.amount AS serving_base, meal_serving.amount AS amount, serving.unit AS unit, serving.relative AS relative FROM meal_serving JOIN serving ON meal_serving.serving_id = serving.id JOIN food ON serving.food_id = food.id WHERE meal_serving.meal_id = $1 ORDER BY meal_serving.position, meal_serving.id")
+        .bind(meal_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
     let mut foods_info: Vec<MealInfoFood> = vec![];
     for food in foods {
         let food_id: i32 = food.try_get("id").ok()?;
         let food_name: String = food.try_get("name").ok()?;
         let meal_serving_id: i32 = food.try_get("meal_serving_id").ok()?;
         let serving_id: i32 = food.try_get("serving_id").ok()?;
-        let mut serving_base: f64 = food.try_get("serving_base").ok()?;
+        let serving_base: f64 = food.try_get("serving_base").ok()?;
         let serving_amount: f64 = food.try_get("amount").ok()?;
         let serving_unit: String = food.try_get("unit").ok()?;
         let serving_relative: Option<i32> = food.try_get("relative").ok()?;
-        let mut serving_rel_amount: f64 = 0.0;
-
-        if serving_relative.is_some() {
-            serving_base = 1.0;
-
-            let row = match sqlx::query("SELECT amount FROM serving WHERE serving.id = $1")
-                .bind(serving_id)
-                .fetch_one(dbpool)
-                .await
-            {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
-
-            serving_rel_amount = row.try_get("amount").unwrap();
-        }
-
-        let query_nutrients = async {
-            if let Some(id) = serving_relative {
-                sqlx::query("SELECT nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit, serving.amount AS serving_base_amount FROM serving_nutrient JOIN serving ON serving.id = serving_nutrient.serving_id JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving.id = $1")
-					.bind(id)
-					.fetch_all(dbpool)
-					.await
-            } else {
-                sqlx::query("SELECT nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit FROM serving_nutrient JOIN serving ON serving.id = serving_nutrient.serving_id JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving.id = $1")
-					.bind(serving_id)
-					.fetch_all(dbpool)
-					.await
-            }
-        };
 
-        let nutrients = match query_nutrients.await {
-            Ok(r) => r,
-            Err(_) => continue,
+        let (serving_base, base_nutrients) = match fetch_serving_base_nutrients(
+            serving_id,
+            serving_base,
+            serving_relative,
+            dbpool,
+        )
+        .await
+        {
+            Some(r) => r,
+            None => continue,
         };
 
-        let mut base_nutrients: Vec<MealInfoNutrient> = vec![];
-        for nutrient in nutrients {
-            let nutrient_name: String = nutrient.try_get("name").ok()?;
-            let mut nutrient_amount: f64 = nutrient.try_get("amount").ok()?;
-            let nutrient_unit: String = nutrient.try_get("unit").ok()?;
-
-            if serving_relative.is_some() {
-                let serving_base_amount: f64 = nutrient.try_get("serving_base_amount").ok()?;
-                nutrient_amount *= serving_rel_amount / serving_base_amount;
-            }
-
-            let nutrient_info = MealInfoNutrient {
-                name: nutrient_name,
-                amount: nutrient_amount,
-                unit: nutrient_unit,
-            };
-            base_nutrients.push(nutrient_info);
-        }
-
         let food = MealInfoFood {
             id: food_id,
             name: food_name,
@@ -246,41 +533,33 @@ pub async fn fetch_user_account(user_id: i32, dbpool: &PgPool) -> Option<UserAcc
     Some(user)
 }
 
-pub async fn create_diet(user_id: i32, diet_name: &String, dbpool: &PgPool) -> Option<()> {
-    let diet_id = sqlx::query("INSERT INTO diet(name, user_id) VALUES($1, $2) RETURNING id")
-        .bind(diet_name)
-        .bind(user_id)
-        .fetch_one(dbpool)
+pub async fn create_diet(user_id: i32, diet_name: &String, dbpool: &PgPool) -> Result<()> {
+    let user_account = fetch_user_account(user_id, dbpool)
         .await
-        .ok()?;
-    let diet_id: i32 = diet_id.try_get("id").ok()?;
-
-    let user_account = fetch_user_account(user_id, dbpool).await?;
+        .ok_or_else(|| Error::msg("User account not found"))?;
 
     let user_age = calculate_age(&user_account.birthdate);
 
-    // TODO: Check if 'query_as' fails if the SQL returns no rows
-    let default_nutrition = sqlx::query_as::<_, DefaultNutrient>("SELECT * FROM default_nutrition WHERE gender = $1 AND $2 >= age_min AND ($3 < age_max OR age_max IS NULL)")
-        .bind(user_account.gender)
-        .bind(user_age)
+    let mut tx = dbpool.begin().await?;
+
+    let query_result = sqlx::query("INSERT INTO diet(name, user_id) VALUES($1, $2) RETURNING id")
+        .bind(diet_name)
+        .bind(user_id)
+        .fetch_one(&mut tx)
+        .await?;
+    let diet_id: i32 = query_result.try_get("id")?;
+
+    // Start the diet with the default nutrition targets for the user's gender and age
+    sqlx::query("INSERT INTO diet_nutrition(diet_id, nutrient_id, min_intake, max_intake, relative) SELECT $1, nutrient_id, min_intake, max_intake, relative FROM default_nutrition WHERE gender = $2 AND $3 >= age_min AND ($3 < age_max OR age_max IS NULL)")
+        .bind(diet_id)
+        .bind(&user_account.gender)
         .bind(user_age)
-        .fetch_all(dbpool)
-        .await
-        .ok()?;
+        .execute(&mut tx)
+        .await?;
 
-    for nutrient in default_nutrition {
-        sqlx::query("INSERT INTO diet_nutrition(diet_id, nutrient_id, min_intake, max_intake, relative) VALUES ($1, $2, $3, $4, $5)")
-            .bind(diet_id)
-            .bind(nutrient.nutrient_id)
-            .bind(nutrient.min_intake)
-            .bind(nutrient.max_intake)
-            .bind(nutrient.relative)
-            .execute(dbpool)
-            .await
-            .ok();
-    }
+    tx.commit().await?;
 
-    Some(())
+    Ok(())
 }
 
 pub async fn edit_diet(diet_id: i32, diet_name: &String, dbpool: &PgPool) -> Option<()> {
@@ -295,31 +574,36 @@ pub async fn edit_diet(diet_id: i32, diet_name: &String, dbpool: &PgPool) -> Opt
 }
 
 pub async fn delete_diet(diet_id: i32, dbpool: &PgPool) -> Result<()> {
-    sqlx::query("DELETE FROM meal_serving WHERE meal_id IN (SELECT meal_serving.meal_id FROM meal_serving JOIN meal ON meal.id = meal_serving.meal_id WHERE diet_id = $1)")
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("DELETE FROM meal_serving WHERE meal_id IN (SELECT id FROM meal WHERE diet_id = $1)")
         .bind(diet_id)
-        .execute(dbpool)
+        .execute(&mut tx)
         .await?;
 
     sqlx::query("DELETE FROM meal WHERE diet_id = $1")
         .bind(diet_id)
-        .execute(dbpool)
+        .execute(&mut tx)
         .await?;
 
     sqlx::query("DELETE FROM diet_nutrition WHERE diet_id = $1")
         .bind(diet_id)
-        .execute(dbpool)
+        .execute(&mut tx)
         .await?;
 
     sqlx::query("DELETE FROM diet WHERE id = $1")
         .bind(diet_id)
-        .execute(dbpool)
+        .execute(&mut tx)
         .await?;
 
+    tx.commit().await?;
+
     Ok(())
 }
 
 pub async fn create_meal(diet_id: i32, meal_name: &String, dbpool: &PgPool) -> Option<i32> {
-    let query_result = sqlx::query("INSERT INTO meal(diet_id, name) VALUES ($1, $2) RETURNING id")
+    // New meals go after the other meals of the diet
+    let query_result = sqlx::query("INSERT INTO meal(diet_id, name, position) VALUES ($1, $2, (SELECT COALESCE(MAX(position) + 1, 0) FROM meal WHERE diet_id = $1)) RETURNING id")
         .bind(diet_id)
         .bind(meal_name)
         .fetch_one(dbpool)
@@ -331,25 +615,30 @@ pub async fn create_meal(diet_id: i32, meal_name: &String, dbpool: &PgPool) -> O
     Some(meal_id)
 }
 
-pub async fn get_meal_user_id(meal_id: i32, dbpool: &PgPool) -> Option<i32> {
-    let query_result = sqlx::query("SELECT diet_id FROM meal WHERE id = $1")
+pub async fn fetch_meal(meal_id: i32, dbpool: &PgPool) -> Option<Meal> {
+    let meal = sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE id = $1")
         .bind(meal_id)
         .fetch_one(dbpool)
         .await
         .ok()?;
 
-    let diet_id: i32 = query_result.try_get("diet_id").ok()?;
-
-    get_diet_user_id(diet_id, dbpool).await
+    Some(meal)
 }
 
-pub async fn delete_meal(meal_id: i32, dbpool: &PgPool) -> Result<()> {
-    sqlx::query("DELETE FROM meal_serving WHERE meal_id = $1")
-        .bind(meal_id)
+/// Sets the order of the meals of a diet to the order of 'meal_ids'
+pub async fn reorder_meals(diet_id: i32, meal_ids: &[i32], dbpool: &PgPool) -> Result<()> {
+    sqlx::query("UPDATE meal SET position = (ordered.position - 1)::INTEGER FROM UNNEST($1::INTEGER[]) WITH ORDINALITY AS ordered(id, position) WHERE meal.id = ordered.id AND meal.diet_id = $2")
+        .bind(meal_ids)
+        .bind(diet_id)
         .execute(dbpool)
         .await?;
 
-    sqlx::query("DELETE FROM meal WHERE id = $1")
+    Ok(())
+}
+
+pub async fn edit_meal(meal_id: i32, meal_name: &String, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("UPDATE meal SET name = $1 WHERE id = $2")
+        .bind(meal_name)
         .bind(meal_id)
         .execute(dbpool)
         .await?;
@@ -357,112 +646,1267 @@ pub async fn delete_meal(meal_id: i32, dbpool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn fetch_search_food_servings(
-    food_id: i32,
-    dbpool: &PgPool,
-) -> Option<Vec<SearchFoodServing>> {
-    let mut search_servings: Vec<SearchFoodServing> = vec![];
+/// Inserts a copy of a meal and its servings in 'diet_id', at 'position'
+async fn insert_meal_copy(
+    meal: &Meal,
+    diet_id: i32,
+    position: i32,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<i32> {
+    let query_result = sqlx::query("INSERT INTO meal(diet_id, name, position) VALUES ($1, $2, $3) RETURNING id")
+        .bind(diet_id)
+        .bind(&meal.name)
+        .bind(position)
+        .fetch_one(&mut *tx)
+        .await?;
 
-    let servings = sqlx::query_as::<_, Serving>("SELECT * FROM serving WHERE food_id = $1")
-        .bind(food_id)
-        .fetch_all(dbpool)
-        .await
-        .ok()?;
+    let new_meal_id: i32 = query_result.try_get("id")?;
 
-    for serving in servings {
-        let mut nutrients: Vec<MealInfoNutrient> = vec![];
+    sqlx::query("INSERT INTO meal_serving(meal_id, serving_id, amount, position) SELECT $1, serving_id, amount, position FROM meal_serving WHERE meal_id = $2")
+        .bind(new_meal_id)
+        .bind(meal.id)
+        .execute(&mut *tx)
+        .await?;
 
-        if serving.relative.is_none() {
-            nutrients = sqlx::query_as::<_, MealInfoNutrient>("SELECT nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit FROM serving_nutrient JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving_id = $1")
-                .bind(serving.id)
-                .fetch_all(dbpool)
-                .await
-                .ok()?;
-        }
+    Ok(new_meal_id)
+}
 
-        search_servings.push(SearchFoodServing {
-            id: serving.id,
-            amount: serving.amount,
-            unit: serving.unit,
-            nutrients,
-            relative: serving.relative,
-        });
-    }
+/// Copies a meal and its servings to the end of 'diet_id' (which may be the diet of the meal)
+pub async fn copy_meal(meal: &Meal, diet_id: i32, dbpool: &PgPool) -> Result<i32> {
+    let mut tx = dbpool.begin().await?;
 
-    Some(search_servings)
-}
+    let query_result = sqlx::query("SELECT COALESCE(MAX(position) + 1, 0) AS position FROM meal WHERE diet_id = $1")
+        .bind(diet_id)
+        .fetch_one(&mut tx)
+        .await?;
 
-pub async fn fetch_search_food(food_id: i32, dbpool: &PgPool) -> Option<SearchFood> {
-    let food = sqlx::query_as::<_, Food>("SELECT * FROM food WHERE id = $1")
-        .bind(food_id)
-        .fetch_one(dbpool)
-        .await
-        .ok()?;
+    let position: i32 = query_result.try_get("position")?;
+    let new_meal_id = insert_meal_copy(meal, diet_id, position, &mut tx).await?;
 
-    let search_servings = fetch_search_food_servings(food.id, dbpool).await?;
+    tx.commit().await?;
 
-    Some(SearchFood {
-        id: food.id,
-        name: food.name.clone(),
-        servings: search_servings,
-    })
+    Ok(new_meal_id)
 }
 
-pub async fn search_foods(food_name: &String, dbpool: &PgPool) -> Option<Vec<SearchFood>> {
-    // setup SQL search strings (for the ILIKE keyword)
-    let best_search = format!("{}%", food_name);
-    let second_best_search = best_search.replace(' ', "%");
-    let food_name_search = format!("%{}", second_best_search);
+/// Moves a meal, along with its servings, to the end of another diet (a meal that is already in
+/// 'diet_id' keeps its position)
+pub async fn move_meal(meal_id: i32, diet_id: i32, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
 
-    // TODO: Optimize. The following query reads all information from 'food', but
-    // 'fetch_search_food' reads that again.
-    let foods = sqlx::query_as::<_, Food>("SELECT * FROM food WHERE name ILIKE $1 ORDER BY (CASE WHEN name ILIKE $2 THEN 1 WHEN name ILIKE $3 THEN 2 ELSE 3 END) ASC LIMIT 10")
-        .bind(food_name_search)
-        .bind(best_search)
-        .bind(second_best_search)
-        .fetch_all(dbpool)
-        .await
-        .ok()?;
+    let query_result = sqlx::query("SELECT diet_id FROM meal WHERE id = $1 FOR UPDATE")
+        .bind(meal_id)
+        .fetch_one(&mut tx)
+        .await?;
 
-    let mut search_foods: Vec<SearchFood> = vec![];
-    for food in foods {
-        search_foods.push(fetch_search_food(food.id, dbpool).await?);
+    let current_diet_id: i32 = query_result.try_get("diet_id")?;
+    if current_diet_id == diet_id {
+        return Ok(());
     }
 
-    Some(search_foods)
-}
+    // Concurrent moves to the same diet wait here, so they can't take the same position (the diet
+    // itself is locked too, in case it has no meals yet)
+    sqlx::query("SELECT id FROM diet WHERE id = $1 FOR UPDATE")
+        .bind(diet_id)
+        .execute(&mut tx)
+        .await?;
 
-pub async fn add_meal_serving(
-    meal_id: i32,
+    let rows = sqlx::query("SELECT position FROM meal WHERE diet_id = $1 FOR UPDATE")
+        .bind(diet_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let positions = rows
+        .iter()
+        .map(|row| row.try_get("position"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    sqlx::query("UPDATE meal SET diet_id = $1, position = $2 WHERE id = $3")
+        .bind(diet_id)
+        .bind(positions.iter().max().map_or(0, |position| position + 1))
+        .bind(meal_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_meal_user_id(meal_id: i32, dbpool: &PgPool) -> Option<i32> {
+    let query_result = sqlx::query("SELECT diet_id FROM meal WHERE id = $1")
+        .bind(meal_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    let diet_id: i32 = query_result.try_get("diet_id").ok()?;
+
+    get_diet_user_id(diet_id, dbpool).await
+}
+
+pub async fn delete_meal(meal_id: i32, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("DELETE FROM meal_serving WHERE meal_id = $1")
+        .bind(meal_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM meal WHERE id = $1")
+        .bind(meal_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Loads the servings, nutrients, barcodes and recipe flags of many foods at once (in a constant number of queries),
+/// keeping the order of 'foods'
+async fn load_search_foods(foods: Vec<Food>, dbpool: &PgPool) -> Option<Vec<SearchFood>> {
+    let food_ids: Vec<i32> = foods.iter().map(|food| food.id).collect();
+
+    let servings = sqlx::query_as::<_, Serving>("SELECT * FROM serving WHERE food_id = ANY($1) ORDER BY id")
+        .bind(&food_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let serving_ids: Vec<i32> = servings.iter().map(|serving| serving.id).collect();
+    let nutrient_rows = sqlx::query("SELECT serving_nutrient.serving_id AS serving_id, nutrient.name AS name, serving_nutrient.amount AS amount, nutrient.unit AS unit FROM serving_nutrient JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving_nutrient.serving_id = ANY($1) ORDER BY nutrient.id")
+        .bind(&serving_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let barcode_rows = sqlx::query("SELECT food_id, code FROM food_barcode WHERE food_id = ANY($1) ORDER BY code")
+        .bind(&food_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let recipe_rows = sqlx::query("SELECT food_id FROM recipe WHERE food_id = ANY($1)")
+        .bind(&food_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let recipe_ids = recipe_rows
+        .iter()
+        .map(|row| row.try_get("food_id"))
+        .collect::<Result<HashSet<i32>, _>>()
+        .ok()?;
+
+    let mut serving_nutrients: HashMap<i32, Vec<MealInfoNutrient>> = HashMap::new();
+    for row in nutrient_rows {
+        serving_nutrients
+            .entry(row.try_get("serving_id").ok()?)
+            .or_default()
+            .push(MealInfoNutrient {
+                name: row.try_get("name").ok()?,
+                amount: row.try_get("amount").ok()?,
+                unit: row.try_get("unit").ok()?,
+            });
+    }
+
+    let mut food_servings: HashMap<i32, Vec<SearchFoodServing>> = HashMap::new();
+    for serving in servings {
+        // relative servings take the nutrients from the serving they are relative to
+        let nutrients = match serving.relative {
+            Some(_) => vec![],
+            None => serving_nutrients.remove(&serving.id).unwrap_or_default(),
+        };
+
+        food_servings
+            .entry(serving.food_id)
+            .or_default()
+            .push(SearchFoodServing {
+                id: serving.id,
+                amount: serving.amount,
+                unit: serving.unit,
+                nutrients,
+                relative: serving.relative,
+            });
+    }
+
+    let mut food_barcodes: HashMap<i32, Vec<String>> = HashMap::new();
+    for row in barcode_rows {
+        food_barcodes
+            .entry(row.try_get("food_id").ok()?)
+            .or_default()
+            .push(row.try_get("code").ok()?);
+    }
+
+    let search_foods = foods
+        .into_iter()
+        .map(|food| SearchFood {
+            servings: food_servings.remove(&food.id).unwrap_or_default(),
+            barcodes: food_barcodes.remove(&food.id).unwrap_or_default(),
+            recipe: recipe_ids.contains(&food.id),
+            density: food.density,
+            id: food.id,
+            name: food.name,
+        })
+        .collect();
+
+    Some(search_foods)
+}
+
+pub async fn fetch_search_food(
+    food_id: i32,
+    user_id: Option<i32>,
+    dbpool: &PgPool,
+) -> Option<SearchFood> {
+    let food = sqlx::query_as::<_, Food>(
+        "SELECT * FROM food WHERE id = $1 AND (user_id = $2 OR user_id = $3)",
+    )
+    .bind(food_id)
+    .bind(CATALOG_USER_ID)
+    .bind(user_id)
+    .fetch_one(dbpool)
+    .await
+    .ok()?;
+
+    load_search_foods(vec![food], dbpool).await?.pop()
+}
+
+/// Fetches the private foods created by a user
+pub async fn fetch_user_foods(user_id: i32, dbpool: &PgPool) -> Option<Vec<SearchFood>> {
+    let foods = sqlx::query_as::<_, Food>("SELECT * FROM food WHERE user_id = $1 ORDER BY id")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    load_search_foods(foods, dbpool).await
+}
+
+/// Nutrient amount of the base serving of a food in a search query (see 'search_foods')
+fn push_normalized_amount(query: &mut QueryBuilder<Postgres>, nutrient_id: i32, basis: NutrientBasis) {
+    query.push("(COALESCE((SELECT amount FROM serving_nutrient WHERE serving_id = base_serving.id AND nutrient_id = ");
+    query.push_bind(nutrient_id);
+    query.push("), 0)");
+    if basis == NutrientBasis::Per100g {
+        query.push(" * 100 / base_serving.amount");
+    }
+    query.push(")");
+}
+
+/// Pushes the base servings, and the foods that match the search (the same for the results and their count)
+fn push_search_foods_source<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    food_name: Option<&'a str>,
+    user_id: Option<i32>,
+    filters: &FoodSearchFilters,
+) {
+    query.push(" FROM food JOIN base_serving ON base_serving.food_id = food.id");
+
+    if let Some(food_name) = food_name {
+        query.push(", websearch_to_tsquery('english', ");
+        query.push_bind(food_name);
+        query.push(") AS search_query");
+    }
+
+    query.push(" WHERE ");
+    match filters.owner {
+        FoodOwner::All => {
+            query.push("(food.user_id = ");
+            query.push_bind(CATALOG_USER_ID);
+            query.push(" OR food.user_id = ");
+            query.push_bind(user_id);
+            query.push(")");
+        }
+        FoodOwner::Catalog => {
+            query.push("food.user_id = ");
+            query.push_bind(CATALOG_USER_ID);
+        }
+        FoodOwner::Mine => {
+            query.push("food.user_id = ");
+            query.push_bind(user_id);
+        }
+    }
+
+    match filters.source {
+        FoodSource::All => {}
+        FoodSource::Usda => {
+            query.push(" AND food.fdc_id IS NOT NULL");
+        }
+        FoodSource::Custom => {
+            query.push(" AND food.fdc_id IS NULL");
+        }
+    }
+
+    if let Some(food_name) = food_name {
+        // NOTE: 'to_tsvector' must match the expression of the 'food_name_fts' index
+        query.push(" AND (to_tsvector('english', food.name) @@ search_query OR ");
+        query.push_bind(food_name);
+        query.push(" <% food.name)");
+    }
+
+    if filters.basis == NutrientBasis::Per100g && (!filters.nutrients.is_empty() || filters.sort_nutrient.is_some()) {
+        query.push(" AND base_serving.unit = 'g'");
+    }
+
+    for filter in &filters.nutrients {
+        if let Some(min) = filter.min {
+            query.push(" AND ");
+            push_normalized_amount(query, filter.nutrient_id, filters.basis);
+            query.push(" >= ");
+            query.push_bind(min);
+        }
+
+        if let Some(max) = filter.max {
+            query.push(" AND ");
+            push_normalized_amount(query, filter.nutrient_id, filters.basis);
+            query.push(" <= ");
+            query.push_bind(max);
+        }
+    }
+}
+
+/// Searches the public catalog, plus the private foods of 'user_id' (if any).
+/// Uses full-text search (e.g 'chicken breast raw' matches 'Chicken, broilers, breast, meat only, raw')
+/// and trigram similarity for partial words. Without a name, only the filters are applied.
+/// Returns a page of results and the total count (None if the page is out of range).
+pub async fn search_foods(
+    food_name: Option<&str>,
+    user_id: Option<i32>,
+    filters: &FoodSearchFilters,
+    page: i64,
+    limit: i64,
+    dbpool: &PgPool,
+) -> Option<(Vec<SearchFood>, i64)> {
+    let offset = page.checked_sub(1)?.checked_mul(limit)?;
+
+    // Nutrient amounts are taken from the base serving (the first non-relative serving),
+    // normalized to 100g (when it is in grams) or kept per serving. Missing nutrients count as 0.
+    const BASE_SERVING: &str = "WITH base_serving AS (SELECT DISTINCT ON (food_id) food_id, id, unit, amount FROM serving WHERE relative IS NULL ORDER BY food_id, id)";
+
+    // Counted apart from the page, which may be past the last result
+    let mut query = QueryBuilder::<Postgres>::new(BASE_SERVING);
+    query.push(" SELECT COUNT(*) AS total");
+    push_search_foods_source(&mut query, food_name, user_id, filters);
+    let total: i64 = query.build().fetch_one(dbpool).await.ok()?.try_get("total").ok()?;
+
+    if offset >= total {
+        return Some((vec![], total));
+    }
+
+    let mut query = QueryBuilder::<Postgres>::new(BASE_SERVING);
+    query.push(" SELECT food.id, food.name, food.user_id, food.fdc_id, food.density");
+    push_search_foods_source(&mut query, food_name, user_id, filters);
+
+    query.push(" ORDER BY ");
+    if let Some(nutrient_id) = filters.sort_nutrient {
+        push_normalized_amount(&mut query, nutrient_id, filters.basis);
+        query.push(match filters.sort_order {
+            SortOrder::Asc => " ASC, ",
+            SortOrder::Desc => " DESC, ",
+        });
+    }
+
+    // The favorites of the user come first, then the foods they use the most
+    if let (true, Some(user_id)) = (filters.personalize, user_id) {
+        query.push("EXISTS (SELECT 1 FROM food_favorite WHERE food_favorite.user_id = ");
+        query.push_bind(user_id);
+        query.push(" AND food_favorite.food_id = food.id) DESC, COALESCE((SELECT SUM(serving_usage.use_count) FROM serving_usage JOIN serving ON serving.id = serving_usage.serving_id WHERE serving_usage.user_id = ");
+        query.push_bind(user_id);
+        query.push(" AND serving.food_id = food.id), 0) DESC, ");
+    }
+
+    if let Some(food_name) = food_name {
+        query.push("ts_rank(to_tsvector('english', food.name), search_query) + word_similarity(");
+        query.push_bind(food_name);
+        query.push(", food.name) DESC, ");
+    }
+
+    query.push("LENGTH(food.name), food.id LIMIT ");
+    query.push_bind(limit);
+    query.push(" OFFSET ");
+    query.push_bind(offset);
+
+    let rows = query.build().fetch_all(dbpool).await.ok()?;
+
+    let foods = rows
+        .iter()
+        .map(Food::from_row)
+        .collect::<Result<Vec<Food>, _>>()
+        .ok()?;
+
+    Some((load_search_foods(foods, dbpool).await?, total))
+}
+
+/// Creates a food owned by 'user_id', along with its base serving
+pub async fn create_food(
+    user_id: i32,
+    food_name: &String,
+    serving_unit: &String,
+    serving_amount: f64,
+    barcode: Option<&String>,
+    dbpool: &PgPool,
+) -> Result<i32> {
+    let mut tx = dbpool.begin().await?;
+
+    let query_result = sqlx::query("INSERT INTO food(name, user_id) VALUES ($1, $2) RETURNING id")
+        .bind(food_name)
+        .bind(user_id)
+        .fetch_one(&mut tx)
+        .await?;
+
+    let food_id: i32 = query_result.try_get("id")?;
+
+    sqlx::query("INSERT INTO serving(food_id, unit, amount, relative) VALUES ($1, $2, $3, NULL)")
+        .bind(food_id)
+        .bind(serving_unit)
+        .bind(serving_amount)
+        .execute(&mut tx)
+        .await?;
+
+    if let Some(code) = barcode {
+        sqlx::query("INSERT INTO food_barcode(food_id, code) VALUES ($1, $2)")
+            .bind(food_id)
+            .bind(code)
+            .execute(&mut tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(food_id)
+}
+
+/// Finds the food with a barcode, among the public catalog and the private foods of 'user_id' (if any).
+/// The private foods come first, since the user may have corrected a catalog food.
+pub async fn find_barcode_food_id(code: &String, user_id: Option<i32>, dbpool: &PgPool) -> Option<i32> {
+    let row = sqlx::query("SELECT food.id AS id FROM food_barcode JOIN food ON food.id = food_barcode.food_id WHERE food_barcode.code = $1 AND (food.user_id = $2 OR food.user_id = $3) ORDER BY (food.user_id = $3) DESC, food.id LIMIT 1")
+        .bind(code)
+        .bind(CATALOG_USER_ID)
+        .bind(user_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    row.try_get("id").ok()
+}
+
+pub async fn add_food_barcode(food_id: i32, code: &String, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("INSERT INTO food_barcode(food_id, code) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(food_id)
+        .bind(code)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_food_barcode(food_id: i32, code: &String, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM food_barcode WHERE food_id = $1 AND code = $2")
+        .bind(food_id)
+        .bind(code)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_food_user_id(food_id: i32, dbpool: &PgPool) -> Option<i32> {
+    let query_result = sqlx::query("SELECT user_id FROM food WHERE id = $1")
+        .bind(food_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    query_result.try_get::<i32, _>("user_id").ok()
+}
+
+/// Renames a food, and sets its density if one is given
+pub async fn edit_food(
+    food_id: i32,
+    food_name: &String,
+    density: Option<f64>,
+    dbpool: &PgPool,
+) -> Result<()> {
+    sqlx::query("UPDATE food SET name = $1, density = COALESCE($2, density) WHERE id = $3")
+        .bind(food_name)
+        .bind(density)
+        .bind(food_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+/// Keeps the diary history of servings that are about to be deleted: the entries that use them get a copy
+/// of the food name, the serving and its nutrients, and no longer reference the serving
+async fn detach_diary_entries(serving_ids: &[i32], tx: &mut Transaction<'_, Postgres>) -> Result<()> {
+    // Relative servings take the nutrients of the serving they are relative to, and have a base of 1
+    sqlx::query("INSERT INTO diary_entry_nutrient(diary_entry_id, nutrient_id, amount) SELECT diary_entry.id, serving_nutrient.nutrient_id, serving_nutrient.amount * CASE WHEN serving.relative IS NULL THEN 1 ELSE serving.amount / base.amount END FROM diary_entry JOIN serving ON serving.id = diary_entry.serving_id JOIN serving AS base ON base.id = COALESCE(serving.relative, serving.id) JOIN serving_nutrient ON serving_nutrient.serving_id = base.id WHERE diary_entry.serving_id = ANY($1)")
+        .bind(serving_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE diary_entry SET food_name = food.name, serving_unit = serving.unit, serving_base = CASE WHEN serving.relative IS NULL THEN serving.amount ELSE 1 END, serving_id = NULL FROM serving JOIN food ON food.id = serving.food_id WHERE serving.id = diary_entry.serving_id AND diary_entry.serving_id = ANY($1)")
+        .bind(serving_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes a food, its servings and every meal serving (or recipe ingredient) that uses them.
+/// The diary entries that use them keep a copy of the food (see 'detach_diary_entries').
+pub async fn delete_food(food_id: i32, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    let rows = sqlx::query("DELETE FROM recipe_ingredient WHERE serving_id IN (SELECT id FROM serving WHERE food_id = $1) RETURNING recipe_id")
+        .bind(food_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let recipe_ids = rows
+        .iter()
+        .map(|row| row.try_get("recipe_id"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    // The food may be a recipe itself
+    sqlx::query("DELETE FROM recipe_ingredient WHERE recipe_id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM recipe WHERE food_id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM meal_serving WHERE serving_id IN (SELECT id FROM serving WHERE food_id = $1)")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    let rows = sqlx::query("SELECT id FROM serving WHERE food_id = $1")
+        .bind(food_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let serving_ids = rows
+        .iter()
+        .map(|row| row.try_get("id"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    detach_diary_entries(&serving_ids, &mut tx).await?;
+
+    sqlx::query("DELETE FROM serving_usage WHERE serving_id IN (SELECT id FROM serving WHERE food_id = $1)")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM food_favorite WHERE food_id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM serving_nutrient WHERE serving_id IN (SELECT id FROM serving WHERE food_id = $1)")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    // Relative servings reference the other servings, so they go first
+    sqlx::query("DELETE FROM serving WHERE food_id = $1 AND relative IS NOT NULL")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM serving WHERE food_id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM food_barcode WHERE food_id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM food WHERE id = $1")
+        .bind(food_id)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&recipe_ids, &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn fetch_serving(serving_id: i32, dbpool: &PgPool) -> Option<Serving> {
+    let serving = sqlx::query_as::<_, Serving>("SELECT * FROM serving WHERE id = $1")
+        .bind(serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(serving)
+}
+
+pub async fn get_serving_user_id(serving_id: i32, dbpool: &PgPool) -> Option<i32> {
+    let query_result = sqlx::query("SELECT food_id FROM serving WHERE id = $1")
+        .bind(serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    let food_id: i32 = query_result.try_get("food_id").ok()?;
+
+    get_food_user_id(food_id, dbpool).await
+}
+
+/// Fetches the unit in which amounts of a serving are counted, for converting amounts given in other units
+pub async fn fetch_serving_unit(serving_id: i32, dbpool: &PgPool) -> Option<ServingUnit> {
+    let row = sqlx::query("SELECT serving.unit AS unit, serving.amount AS amount, base.unit AS base_unit, food.density AS density FROM serving JOIN food ON food.id = serving.food_id LEFT JOIN serving AS base ON base.id = serving.relative WHERE serving.id = $1")
+        .bind(serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    let base_unit: Option<String> = row.try_get("base_unit").ok()?;
+    let amount: f64 = row.try_get("amount").ok()?;
+
+    Some(ServingUnit {
+        unit: row.try_get("unit").ok()?,
+        base: base_unit.map(|unit| (unit, amount)),
+        density: row.try_get("density").ok()?,
+    })
+}
+
+/// Checks if 'serving_id' can be made relative to 'relative_id'. The relative serving
+/// must be a non-relative serving of the same food, and no serving can be relative to
+/// a serving that is relative itself.
+pub async fn check_relative_serving(
+    food_id: i32,
+    serving_id: Option<i32>,
+    relative_id: i32,
+    dbpool: &PgPool,
+) -> bool {
+    if serving_id == Some(relative_id) {
+        return false;
+    }
+
+    let relative_serving = match fetch_serving(relative_id, dbpool).await {
+        Some(serving) => serving,
+        None => return false,
+    };
+
+    if relative_serving.food_id != food_id || relative_serving.relative.is_some() {
+        return false;
+    }
+
+    let serving_id = match serving_id {
+        Some(id) => id,
+        None => return true,
+    };
+
+    let dependents = sqlx::query("SELECT COUNT(*) AS count FROM serving WHERE relative = $1")
+        .bind(serving_id)
+        .fetch_one(dbpool)
+        .await
+        .and_then(|row| row.try_get::<i64, _>("count"));
+
+    matches!(dependents, Ok(0))
+}
+
+pub async fn create_serving(
+    food_id: i32,
+    unit: &String,
+    amount: f64,
+    relative: Option<i32>,
+    dbpool: &PgPool,
+) -> Result<i32> {
+    let query_result = sqlx::query("INSERT INTO serving(food_id, unit, amount, relative) VALUES ($1, $2, $3, $4) RETURNING id")
+        .bind(food_id)
+        .bind(unit)
+        .bind(amount)
+        .bind(relative)
+        .fetch_one(dbpool)
+        .await?;
+
+    Ok(query_result.try_get("id")?)
+}
+
+pub async fn update_serving(
+    serving_id: i32,
+    unit: &String,
+    amount: f64,
+    relative: Option<i32>,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("UPDATE serving SET unit = $1, amount = $2, relative = $3 WHERE id = $4")
+        .bind(unit)
+        .bind(amount)
+        .bind(relative)
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    // Relative servings take their nutrients from the serving they are relative to
+    if relative.is_some() {
+        sqlx::query("DELETE FROM serving_nutrient WHERE serving_id = $1")
+            .bind(serving_id)
+            .execute(&mut tx)
+            .await?;
+    }
+
+    let food_id = get_serving_food_id(serving_id, &mut tx).await?;
+    refresh_recipes(&[], &[food_id], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Deletes a serving, the servings relative to it and every meal serving (or recipe ingredient) that uses them.
+/// The diary entries that use them keep a copy of the serving (see 'detach_diary_entries').
+pub async fn delete_serving(serving_id: i32, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    let rows = sqlx::query("DELETE FROM recipe_ingredient WHERE serving_id = $1 OR serving_id IN (SELECT id FROM serving WHERE relative = $1) RETURNING recipe_id")
+        .bind(serving_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let recipe_ids = rows
+        .iter()
+        .map(|row| row.try_get("recipe_id"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    sqlx::query("DELETE FROM meal_serving WHERE serving_id = $1 OR serving_id IN (SELECT id FROM serving WHERE relative = $1)")
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    let rows = sqlx::query("SELECT id FROM serving WHERE id = $1 OR relative = $1")
+        .bind(serving_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let serving_ids = rows
+        .iter()
+        .map(|row| row.try_get("id"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    detach_diary_entries(&serving_ids, &mut tx).await?;
+
+    sqlx::query("DELETE FROM serving_usage WHERE serving_id = $1 OR serving_id IN (SELECT id FROM serving WHERE relative = $1)")
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM serving_nutrient WHERE serving_id = $1")
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM serving WHERE relative = $1")
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM serving WHERE id = $1")
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&recipe_ids, &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn set_serving_nutrient(
+    serving_id: i32,
+    nutrient_id: i32,
+    amount: f64,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("INSERT INTO serving_nutrient(serving_id, nutrient_id, amount) VALUES ($1, $2, $3) ON CONFLICT (serving_id, nutrient_id) DO UPDATE SET amount = EXCLUDED.amount")
+        .bind(serving_id)
+        .bind(nutrient_id)
+        .bind(amount)
+        .execute(&mut tx)
+        .await?;
+
+    let food_id = get_serving_food_id(serving_id, &mut tx).await?;
+    refresh_recipes(&[], &[food_id], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_serving_nutrient(
+    serving_id: i32,
+    nutrient_id: i32,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("DELETE FROM serving_nutrient WHERE serving_id = $1 AND nutrient_id = $2")
+        .bind(serving_id)
+        .bind(nutrient_id)
+        .execute(&mut tx)
+        .await?;
+
+    let food_id = get_serving_food_id(serving_id, &mut tx).await?;
+    refresh_recipes(&[], &[food_id], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Creates a recipe owned by 'user_id'. Its yield serving holds the total weight or number of
+/// portions (e.g '450 g' or '4 portion') and the nutrients calculated from the ingredients.
+pub async fn create_recipe(
+    user_id: i32,
+    recipe_name: &String,
+    yield_unit: &String,
+    yield_amount: f64,
+    dbpool: &PgPool,
+) -> Result<i32> {
+    let mut tx = dbpool.begin().await?;
+
+    let query_result = sqlx::query("INSERT INTO food(name, user_id) VALUES ($1, $2) RETURNING id")
+        .bind(recipe_name)
+        .bind(user_id)
+        .fetch_one(&mut tx)
+        .await?;
+
+    let food_id: i32 = query_result.try_get("id")?;
+
+    let query_result = sqlx::query("INSERT INTO serving(food_id, unit, amount, relative) VALUES ($1, $2, $3, NULL) RETURNING id")
+        .bind(food_id)
+        .bind(yield_unit)
+        .bind(yield_amount)
+        .fetch_one(&mut tx)
+        .await?;
+
+    let serving_id: i32 = query_result.try_get("id")?;
+
+    sqlx::query("INSERT INTO recipe(food_id, serving_id) VALUES ($1, $2)")
+        .bind(food_id)
+        .bind(serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(food_id)
+}
+
+pub async fn fetch_recipe(food_id: i32, dbpool: &PgPool) -> Option<Recipe> {
+    let recipe = sqlx::query_as::<_, Recipe>("SELECT * FROM recipe WHERE food_id = $1")
+        .bind(food_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(recipe)
+}
+
+/// Checks if a serving is the yield serving of a recipe (its nutrients can't be edited directly)
+pub async fn is_recipe_serving(serving_id: i32, dbpool: &PgPool) -> Option<bool> {
+    let query_result = sqlx::query("SELECT EXISTS(SELECT 1 FROM recipe WHERE serving_id = $1) AS recipe")
+        .bind(serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    query_result.try_get("recipe").ok()
+}
+
+pub async fn fetch_recipe_info(food_id: i32, user_id: Option<i32>, dbpool: &PgPool) -> Option<RecipeInfo> {
+    let recipe = sqlx::query("SELECT food.id AS food_id, food.name AS name, serving.id AS serving_id, serving.amount AS yield_amount, serving.unit AS yield_unit FROM recipe JOIN food ON food.id = recipe.food_id JOIN serving ON serving.id = recipe.serving_id WHERE recipe.food_id = $1 AND (food.user_id = $2 OR food.user_id = $3)")
+        .bind(food_id)
+        .bind(CATALOG_USER_ID)
+        .bind(user_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    let ingredients = sqlx::query_as::<_, RecipeInfoIngredient>("SELECT recipe_ingredient.id AS id, food.id AS food_id, food.name AS food_name, serving.id AS serving_id, recipe_ingredient.amount AS amount, serving.unit AS unit FROM recipe_ingredient JOIN serving ON serving.id = recipe_ingredient.serving_id JOIN food ON food.id = serving.food_id WHERE recipe_ingredient.recipe_id = $1 ORDER BY recipe_ingredient.id")
+        .bind(food_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(RecipeInfo {
+        food_id: recipe.try_get("food_id").ok()?,
+        name: recipe.try_get("name").ok()?,
+        serving_id: recipe.try_get("serving_id").ok()?,
+        yield_amount: recipe.try_get("yield_amount").ok()?,
+        yield_unit: recipe.try_get("yield_unit").ok()?,
+        ingredients,
+    })
+}
+
+pub async fn fetch_recipe_ingredient(recipe_ingredient_id: i32, dbpool: &PgPool) -> Option<RecipeIngredient> {
+    let ingredient = sqlx::query_as::<_, RecipeIngredient>("SELECT * FROM recipe_ingredient WHERE id = $1")
+        .bind(recipe_ingredient_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(ingredient)
+}
+
+/// Checks if a serving can be an ingredient of a recipe. The food of the serving can't be
+/// the recipe itself, or another recipe that (directly or not) contains the recipe.
+async fn check_recipe_ingredient(
+    recipe_id: i32,
+    serving_id: i32,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<bool> {
+    // Two ingredients added at the same time could close a cycle that neither of them sees, so the
+    // recipe and the foods it would contain are locked first (any such changes then overlap and wait)
+    sqlx::query("WITH RECURSIVE parts(food_id) AS (SELECT food_id FROM serving WHERE id = $2 UNION SELECT serving.food_id FROM recipe_ingredient JOIN serving ON serving.id = recipe_ingredient.serving_id JOIN parts ON parts.food_id = recipe_ingredient.recipe_id) SELECT id FROM food WHERE id = $1 OR id IN (SELECT food_id FROM parts) ORDER BY id FOR UPDATE")
+        .bind(recipe_id)
+        .bind(serving_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let query_result = sqlx::query("WITH RECURSIVE parts(food_id) AS (SELECT food_id FROM serving WHERE id = $2 UNION SELECT serving.food_id FROM recipe_ingredient JOIN serving ON serving.id = recipe_ingredient.serving_id JOIN parts ON parts.food_id = recipe_ingredient.recipe_id) SELECT EXISTS(SELECT 1 FROM parts WHERE food_id = $1) AS cycle")
+        .bind(recipe_id)
+        .bind(serving_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    Ok(!query_result.try_get::<bool, _>("cycle")?)
+}
+
+/// Recalculates the nutrients of the recipes in 'recipe_ids', and of every recipe that
+/// (directly or through other recipes) uses them or the foods in 'food_ids' as ingredients.
+/// The nutrients of the yield serving are the sum of the ingredients, for the whole yield.
+async fn refresh_recipes(
+    recipe_ids: &[i32],
+    food_ids: &[i32],
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
+    // A recipe is refreshed after all of its ingredients, so they are sorted by their longest
+    // path to a changed food (recipes can't contain themselves, but the depth is capped anyway)
+    let rows = sqlx::query("WITH RECURSIVE dependents(food_id, depth) AS (SELECT food_id, 0 FROM recipe WHERE food_id = ANY($1) UNION SELECT recipe_ingredient.recipe_id, 1 FROM recipe_ingredient JOIN serving ON serving.id = recipe_ingredient.serving_id WHERE serving.food_id = ANY($2) UNION SELECT recipe_ingredient.recipe_id, dependents.depth + 1 FROM recipe_ingredient JOIN serving ON serving.id = recipe_ingredient.serving_id JOIN dependents ON dependents.food_id = serving.food_id WHERE dependents.depth < 32) SELECT food_id FROM dependents GROUP BY food_id ORDER BY MAX(depth)")
+        .bind(recipe_ids)
+        .bind(food_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    for row in rows {
+        let recipe_id: i32 = row.try_get("food_id")?;
+
+        sqlx::query("DELETE FROM serving_nutrient WHERE serving_id = (SELECT serving_id FROM recipe WHERE food_id = $1)")
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Same proportions as the meal servings: relative servings are an amount of the serving they are relative to
+        sqlx::query("INSERT INTO serving_nutrient(serving_id, nutrient_id, amount) SELECT recipe.serving_id, serving_nutrient.nutrient_id, SUM(serving_nutrient.amount * CASE WHEN serving.relative IS NULL THEN recipe_ingredient.amount / serving.amount ELSE recipe_ingredient.amount * serving.amount / base.amount END) FROM recipe JOIN recipe_ingredient ON recipe_ingredient.recipe_id = recipe.food_id JOIN serving ON serving.id = recipe_ingredient.serving_id JOIN serving AS base ON base.id = COALESCE(serving.relative, serving.id) JOIN serving_nutrient ON serving_nutrient.serving_id = base.id WHERE recipe.food_id = $1 GROUP BY recipe.serving_id, serving_nutrient.nutrient_id")
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Gets the food of a serving, for refreshing the recipes that use it
+async fn get_serving_food_id(serving_id: i32, tx: &mut Transaction<'_, Postgres>) -> Result<i32> {
+    let query_result = sqlx::query("SELECT food_id FROM serving WHERE id = $1")
+        .bind(serving_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    Ok(query_result.try_get("food_id")?)
+}
+
+pub async fn update_recipe_yield(
+    recipe: &Recipe,
+    yield_unit: &String,
+    yield_amount: f64,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("UPDATE serving SET unit = $1, amount = $2 WHERE id = $3")
+        .bind(yield_unit)
+        .bind(yield_amount)
+        .bind(recipe.serving_id)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&[recipe.food_id], &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Adds an ingredient to a recipe, unless the recipe would contain itself (returns false)
+pub async fn add_recipe_ingredient(
+    recipe_id: i32,
+    serving_id: i32,
+    amount: f64,
+    dbpool: &PgPool,
+) -> Result<bool> {
+    let mut tx = dbpool.begin().await?;
+
+    if !check_recipe_ingredient(recipe_id, serving_id, &mut tx).await? {
+        return Ok(false);
+    }
+
+    sqlx::query("INSERT INTO recipe_ingredient(recipe_id, serving_id, amount) VALUES ($1, $2, $3)")
+        .bind(recipe_id)
+        .bind(serving_id)
+        .bind(amount)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&[recipe_id], &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Same as 'add_recipe_ingredient', for another serving or amount of an ingredient
+pub async fn update_recipe_ingredient(
+    ingredient: &RecipeIngredient,
+    serving_id: i32,
+    amount: f64,
+    dbpool: &PgPool,
+) -> Result<bool> {
+    let mut tx = dbpool.begin().await?;
+
+    if !check_recipe_ingredient(ingredient.recipe_id, serving_id, &mut tx).await? {
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE recipe_ingredient SET serving_id = $1, amount = $2 WHERE id = $3")
+        .bind(serving_id)
+        .bind(amount)
+        .bind(ingredient.id)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&[ingredient.recipe_id], &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+pub async fn delete_recipe_ingredient(ingredient: &RecipeIngredient, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("DELETE FROM recipe_ingredient WHERE id = $1")
+        .bind(ingredient.id)
+        .execute(&mut tx)
+        .await?;
+
+    refresh_recipes(&[ingredient.recipe_id], &[], &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn add_meal_serving(
+    meal_id: i32,
     serving_id: i32,
     amount: f64,
     dbpool: &PgPool,
 ) -> Result<()> {
-    sqlx::query("INSERT INTO meal_serving(meal_id, serving_id, amount) VALUES ($1, $2, $3)")
+    sqlx::query("INSERT INTO meal_serving(meal_id, serving_id, amount, position) VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0) FROM meal_serving WHERE meal_id = $1))")
+        .bind(meal_id)
+        .bind(serving_id)
+        .bind(amount)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_meal_serving_user_id(meal_serving_id: i32, dbpool: &PgPool) -> Option<i32> {
+    let query_result = sqlx::query("SELECT meal_id FROM meal_serving WHERE id = $1")
+        .bind(meal_serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    let meal_id: i32 = query_result.try_get("meal_id").ok()?;
+
+    get_meal_user_id(meal_id, dbpool).await
+}
+
+pub async fn fetch_meal_serving(meal_serving_id: i32, dbpool: &PgPool) -> Option<MealServing> {
+    let meal_serving = sqlx::query_as::<_, MealServing>("SELECT * FROM meal_serving WHERE id = $1")
+        .bind(meal_serving_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(meal_serving)
+}
+
+pub async fn fetch_meal_servings(meal_id: i32, dbpool: &PgPool) -> Option<Vec<MealServing>> {
+    let meal_servings = sqlx::query_as::<_, MealServing>("SELECT * FROM meal_serving WHERE meal_id = $1 ORDER BY position, id")
+        .bind(meal_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(meal_servings)
+}
+
+/// Puts the servings in 'meal_serving_ids' in 'meal_id', in that order
+async fn set_meal_serving_order(
+    meal_id: i32,
+    meal_serving_ids: &[i32],
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
+    sqlx::query("UPDATE meal_serving SET meal_id = $1, position = (ordered.position - 1)::INTEGER FROM UNNEST($2::INTEGER[]) WITH ORDINALITY AS ordered(id, position) WHERE meal_serving.id = ordered.id")
+        .bind(meal_id)
+        .bind(meal_serving_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the order of the servings of a meal to the order of 'meal_serving_ids'
+pub async fn reorder_meal_servings(meal_id: i32, meal_serving_ids: &[i32], dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    set_meal_serving_order(meal_id, meal_serving_ids, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Moves a meal serving to another meal, at 'position' (or at the end)
+pub async fn move_meal_serving(
+    meal_serving: &MealServing,
+    meal_id: i32,
+    position: Option<usize>,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    let rows = sqlx::query("SELECT id FROM meal_serving WHERE meal_id = $1 AND id <> $2 ORDER BY position, id")
         .bind(meal_id)
+        .bind(meal_serving.id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let mut meal_serving_ids = rows
+        .iter()
+        .map(|row| row.try_get("id"))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    let position = position.map_or(meal_serving_ids.len(), |position| position.min(meal_serving_ids.len()));
+    meal_serving_ids.insert(position, meal_serving.id);
+
+    set_meal_serving_order(meal_id, &meal_serving_ids, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_meal_serving(meal_serving_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM meal_serving WHERE id = $1")
+        .bind(meal_serving_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+/// Remembers that a user used a serving (for the recently used foods)
+pub async fn record_serving_usage(user_id: i32, serving_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("INSERT INTO serving_usage(user_id, serving_id) VALUES ($1, $2) ON CONFLICT (user_id, serving_id) DO UPDATE SET use_count = serving_usage.use_count + 1, last_used = NOW()")
+        .bind(user_id)
         .bind(serving_id)
-        .bind(amount)
         .execute(dbpool)
         .await?;
 
     Ok(())
 }
 
-pub async fn get_meal_serving_user_id(meal_serving_id: i32, dbpool: &PgPool) -> Option<i32> {
-    let query_result = sqlx::query("SELECT meal_id FROM meal_serving WHERE id = $1")
-        .bind(meal_serving_id)
-        .fetch_one(dbpool)
+/// Fetches the foods that a user used the most recently, newest first
+pub async fn fetch_recent_foods(user_id: i32, limit: i64, dbpool: &PgPool) -> Option<Vec<SearchFood>> {
+    let foods = sqlx::query_as::<_, Food>("SELECT food.* FROM food JOIN (SELECT serving.food_id AS food_id, MAX(serving_usage.last_used) AS last_used FROM serving_usage JOIN serving ON serving.id = serving_usage.serving_id WHERE serving_usage.user_id = $1 GROUP BY serving.food_id) AS recent ON recent.food_id = food.id WHERE food.user_id = $1 OR food.user_id = $2 ORDER BY recent.last_used DESC LIMIT $3")
+        .bind(user_id)
+        .bind(CATALOG_USER_ID)
+        .bind(limit)
+        .fetch_all(dbpool)
         .await
         .ok()?;
 
-    let meal_id: i32 = query_result.try_get("meal_id").ok()?;
+    load_search_foods(foods, dbpool).await
+}
 
-    get_meal_user_id(meal_id, dbpool).await
+/// Fetches the foods starred by a user, newest first
+pub async fn fetch_favorite_foods(user_id: i32, dbpool: &PgPool) -> Option<Vec<SearchFood>> {
+    let foods = sqlx::query_as::<_, Food>("SELECT food.* FROM food JOIN food_favorite ON food_favorite.food_id = food.id WHERE food_favorite.user_id = $1 AND (food.user_id = $1 OR food.user_id = $2) ORDER BY food_favorite.created_at DESC")
+        .bind(user_id)
+        .bind(CATALOG_USER_ID)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    load_search_foods(foods, dbpool).await
 }
 
-pub async fn delete_meal_serving(meal_serving_id: i32, dbpool: &PgPool) -> Result<()> {
-    sqlx::query("DELETE FROM meal_serving WHERE id = $1")
-        .bind(meal_serving_id)
+pub async fn fetch_user_favorites(user_id: i32, dbpool: &PgPool) -> Option<Vec<FoodFavorite>> {
+    let favorites = sqlx::query_as::<_, FoodFavorite>("SELECT food_id, created_at FROM food_favorite WHERE user_id = $1 ORDER BY created_at, food_id")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(favorites)
+}
+
+pub async fn fetch_user_serving_usage(user_id: i32, dbpool: &PgPool) -> Option<Vec<ServingUsage>> {
+    let usage = sqlx::query_as::<_, ServingUsage>("SELECT serving_id, use_count, last_used FROM serving_usage WHERE user_id = $1 ORDER BY last_used, serving_id")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(usage)
+}
+
+pub async fn add_favorite_food(user_id: i32, food_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("INSERT INTO food_favorite(user_id, food_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(user_id)
+        .bind(food_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_favorite_food(user_id: i32, food_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM food_favorite WHERE user_id = $1 AND food_id = $2")
+        .bind(user_id)
+        .bind(food_id)
         .execute(dbpool)
         .await?;
 
@@ -490,46 +1934,501 @@ pub async fn duplicate_diet(
     diet_id: i32,
     new_diet_name: &String,
     dbpool: &PgPool,
-) -> Option<()> {
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
     // Create diet
     let query_result = sqlx::query("INSERT INTO diet(name, user_id) VALUES ($1, $2) RETURNING id")
         .bind(new_diet_name)
         .bind(user_id)
-        .fetch_one(dbpool)
-        .await
-        .ok()?;
+        .fetch_one(&mut tx)
+        .await?;
 
-    let new_diet_id: i32 = query_result.try_get("id").ok()?;
+    let new_diet_id: i32 = query_result.try_get("id")?;
 
     // Copy diet nutrition
     sqlx::query("INSERT INTO diet_nutrition(diet_id, nutrient_id, min_intake, max_intake, relative) SELECT $1, nutrient_id, min_intake, max_intake, relative FROM diet_nutrition WHERE diet_id = $2")
         .bind(new_diet_id)
         .bind(diet_id)
+        .execute(&mut tx)
+        .await?;
+
+    // Copy meals
+    let meals = sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE diet_id = $1 ORDER BY position, id")
+        .bind(diet_id)
+        .fetch_all(&mut tx)
+        .await?;
+
+    for meal in meals {
+        insert_meal_copy(&meal, new_diet_id, meal.position, &mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn fetch_diary_entries(
+    user_id: i32,
+    entry_date: NaiveDate,
+    dbpool: &PgPool,
+) -> Option<Vec<DiaryEntryInfo>> {
+    // Entries of deleted foods have no serving, and use their copy of the food instead
+    let entries = sqlx::query("SELECT diary_entry.id AS id, diary_entry.meal_name AS meal_name, food.id AS food_id, COALESCE(food.name, diary_entry.food_name) AS food_name, serving.id AS serving_id, COALESCE(serving.amount, diary_entry.serving_base) AS serving_base, diary_entry.amount AS amount, COALESCE(serving.unit, diary_entry.serving_unit) AS unit, serving.relative AS relative FROM diary_entry LEFT JOIN serving ON diary_entry.serving_id = serving.id LEFT JOIN food ON serving.food_id = food.id WHERE diary_entry.user_id = $1 AND diary_entry.entry_date = $2 ORDER BY diary_entry.id")
+        .bind(user_id)
+        .bind(entry_date)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    // Nutrients of all the servings at once. Relative servings take the nutrients of the serving
+    // they are relative to, scaled to one unit (their base is always 1).
+    let serving_ids: Vec<i32> = entries
+        .iter()
+        .filter_map(|entry| entry.try_get("serving_id").ok().flatten())
+        .collect();
+    let nutrient_rows = sqlx::query("SELECT serving.id AS serving_id, nutrient.name AS name, serving_nutrient.amount * CASE WHEN serving.relative IS NULL THEN 1 ELSE serving.amount / base.amount END AS amount, nutrient.unit AS unit FROM serving JOIN serving AS base ON base.id = COALESCE(serving.relative, serving.id) JOIN serving_nutrient ON serving_nutrient.serving_id = base.id JOIN nutrient ON nutrient.id = serving_nutrient.nutrient_id WHERE serving.id = ANY($1) ORDER BY nutrient.id")
+        .bind(&serving_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let mut serving_nutrients: HashMap<i32, Vec<MealInfoNutrient>> = HashMap::new();
+    for row in nutrient_rows {
+        let nutrient = || -> Option<(i32, MealInfoNutrient)> {
+            Some((
+                row.try_get("serving_id").ok()?,
+                MealInfoNutrient {
+                    name: row.try_get("name").ok()?,
+                    amount: row.try_get("amount").ok()?,
+                    unit: row.try_get("unit").ok()?,
+                },
+            ))
+        };
+
+        match nutrient() {
+            Some((serving_id, nutrient)) => serving_nutrients.entry(serving_id).or_default().push(nutrient),
+            None => error!("Skipping a serving nutrient that couldn't be read: {:?}", row.try_get::<i32, _>("serving_id")),
+        }
+    }
+
+    let detached_ids: Vec<i32> = entries
+        .iter()
+        .filter(|entry| entry.try_get::<Option<i32>, _>("serving_id").is_ok_and(|serving_id| serving_id.is_none()))
+        .filter_map(|entry| entry.try_get("id").ok())
+        .collect();
+    let detached_rows = sqlx::query("SELECT diary_entry_nutrient.diary_entry_id AS diary_entry_id, nutrient.name AS name, diary_entry_nutrient.amount AS amount, nutrient.unit AS unit FROM diary_entry_nutrient JOIN nutrient ON nutrient.id = diary_entry_nutrient.nutrient_id WHERE diary_entry_nutrient.diary_entry_id = ANY($1) ORDER BY nutrient.id")
+        .bind(&detached_ids)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    let mut entry_nutrients: HashMap<i32, Vec<MealInfoNutrient>> = HashMap::new();
+    for row in detached_rows {
+        let nutrient = || -> Option<(i32, MealInfoNutrient)> {
+            Some((
+                row.try_get("diary_entry_id").ok()?,
+                MealInfoNutrient {
+                    name: row.try_get("name").ok()?,
+                    amount: row.try_get("amount").ok()?,
+                    unit: row.try_get("unit").ok()?,
+                },
+            ))
+        };
+
+        match nutrient() {
+            Some((diary_entry_id, nutrient)) => entry_nutrients.entry(diary_entry_id).or_default().push(nutrient),
+            None => error!("Skipping a diary entry nutrient that couldn't be read: {:?}", row.try_get::<i32, _>("diary_entry_id")),
+        }
+    }
+
+    // An entry that can't be read is left out, instead of failing the whole day
+    let mut entries_info: Vec<DiaryEntryInfo> = vec![];
+    for entry in entries {
+        let entry_info = || -> Option<DiaryEntryInfo> {
+            let id: i32 = entry.try_get("id").ok()?;
+            let serving_id: Option<i32> = entry.try_get("serving_id").ok()?;
+            let serving_relative: Option<i32> = entry.try_get("relative").ok()?;
+            let base_nutrients = match serving_id {
+                Some(serving_id) => serving_nutrients.get(&serving_id).cloned(),
+                None => entry_nutrients.get(&id).cloned(),
+            };
+
+            Some(DiaryEntryInfo {
+                id,
+                meal_name: entry.try_get("meal_name").ok()?,
+                food_id: entry.try_get("food_id").ok()?,
+                food_name: entry.try_get("food_name").ok()?,
+                serving_id,
+                serving_base: match serving_relative {
+                    Some(_) => 1.0,
+                    None => entry.try_get("serving_base").ok()?,
+                },
+                serving_amount: entry.try_get("amount").ok()?,
+                serving_unit: entry.try_get("unit").ok()?,
+                base_nutrients: base_nutrients.unwrap_or_default(),
+            })
+        };
+
+        match entry_info() {
+            Some(entry_info) => entries_info.push(entry_info),
+            None => error!("Skipping a diary entry that couldn't be read: {:?}", entry.try_get::<i32, _>("id")),
+        }
+    }
+
+    Some(entries_info)
+}
+
+pub async fn fetch_user_diary(user_id: i32, dbpool: &PgPool) -> Option<Vec<DiaryEntry>> {
+    let entries = sqlx::query_as::<_, DiaryEntry>("SELECT * FROM diary_entry WHERE user_id = $1 ORDER BY entry_date, id")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
+
+    Some(entries)
+}
+
+pub async fn fetch_diary_entry(diary_entry_id: i32, dbpool: &PgPool) -> Option<DiaryEntry> {
+    let entry = sqlx::query_as::<_, DiaryEntry>("SELECT * FROM diary_entry WHERE id = $1")
+        .bind(diary_entry_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(entry)
+}
+
+pub async fn add_diary_entry(
+    user_id: i32,
+    entry_date: NaiveDate,
+    meal_name: Option<&String>,
+    serving_id: i32,
+    amount: f64,
+    dbpool: &PgPool,
+) -> Result<i32> {
+    let query_result = sqlx::query("INSERT INTO diary_entry(user_id, entry_date, meal_name, serving_id, amount) VALUES ($1, $2, $3, $4, $5) RETURNING id")
+        .bind(user_id)
+        .bind(entry_date)
+        .bind(meal_name)
+        .bind(serving_id)
+        .bind(amount)
+        .fetch_one(dbpool)
+        .await?;
+
+    Ok(query_result.try_get("id")?)
+}
+
+pub async fn update_diary_entry(
+    diary_entry_id: i32,
+    entry_date: NaiveDate,
+    meal_name: Option<&String>,
+    serving_id: i32,
+    amount: f64,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    // The entry may have been using a copy of a deleted food, which the new serving replaces
+    sqlx::query("UPDATE diary_entry SET entry_date = $1, meal_name = $2, serving_id = $3, amount = $4, food_name = NULL, serving_unit = NULL, serving_base = NULL WHERE id = $5")
+        .bind(entry_date)
+        .bind(meal_name)
+        .bind(serving_id)
+        .bind(amount)
+        .bind(diary_entry_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query("DELETE FROM diary_entry_nutrient WHERE diary_entry_id = $1")
+        .bind(diary_entry_id)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_diary_entry(diary_entry_id: i32, dbpool: &PgPool) -> Result<()> {
+    sqlx::query("DELETE FROM diary_entry WHERE id = $1")
+        .bind(diary_entry_id)
+        .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+/// Logs every meal serving of a diet as diary entries for 'entry_date'
+pub async fn copy_diet_to_diary(
+    user_id: i32,
+    diet_id: i32,
+    entry_date: NaiveDate,
+    dbpool: &PgPool,
+) -> Result<()> {
+    // A single statement, so the meals and servings are read in the same snapshot as the entries are inserted
+    sqlx::query("INSERT INTO diary_entry(user_id, entry_date, meal_name, serving_id, amount) SELECT $1, $2, meal.name, meal_serving.serving_id, meal_serving.amount FROM meal JOIN meal_serving ON meal_serving.meal_id = meal.id WHERE meal.diet_id = $3 ORDER BY meal.position, meal.id, meal_serving.position, meal_serving.id")
+        .bind(user_id)
+        .bind(entry_date)
+        .bind(diet_id)
         .execute(dbpool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn fetch_weight_entries(
+    user_id: i32,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    dbpool: &PgPool,
+) -> Option<Vec<WeightEntry>> {
+    let entries = sqlx::query_as::<_, WeightEntry>("SELECT * FROM weight_entry WHERE user_id = $1 AND entry_date BETWEEN $2 AND $3 ORDER BY entry_date")
+        .bind(user_id)
+        .bind(from_date)
+        .bind(to_date)
+        .fetch_all(dbpool)
         .await
         .ok()?;
 
-    // Copy meals
-    let meals = fetch_diet_meals(diet_id, dbpool).await?;
-    for meal in meals {
-        let query_result =
-            sqlx::query("INSERT INTO meal(diet_id, name) VALUES ($1, $2) RETURNING id")
-                .bind(new_diet_id)
-                .bind(&meal.name)
-                .fetch_one(dbpool)
-                .await
-                .ok()?;
+    Some(entries)
+}
 
-        let new_meal_id: i32 = query_result.try_get("id").ok()?;
+pub async fn fetch_user_weight_log(user_id: i32, dbpool: &PgPool) -> Option<Vec<WeightEntry>> {
+    let entries = sqlx::query_as::<_, WeightEntry>("SELECT * FROM weight_entry WHERE user_id = $1 ORDER BY entry_date")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .ok()?;
 
-        // Copy meal servings
-        sqlx::query("INSERT INTO meal_serving(meal_id, serving_id, amount) SELECT $1, serving_id, amount FROM meal_serving WHERE meal_id = $2")
-            .bind(new_meal_id)
-            .bind(meal.id)
-            .execute(dbpool)
-            .await
-            .ok()?;
+    Some(entries)
+}
+
+pub async fn fetch_weight_entry(weight_entry_id: i32, dbpool: &PgPool) -> Option<WeightEntry> {
+    let entry = sqlx::query_as::<_, WeightEntry>("SELECT * FROM weight_entry WHERE id = $1")
+        .bind(weight_entry_id)
+        .fetch_one(dbpool)
+        .await
+        .ok()?;
+
+    Some(entry)
+}
+
+/// Makes 'user_account.weight' follow the most recent weight entry
+async fn sync_user_weight(user_id: i32, tx: &mut Transaction<'_, Postgres>) -> Result<()> {
+    sqlx::query("UPDATE user_account SET weight = COALESCE((SELECT weight FROM weight_entry WHERE user_id = $1 ORDER BY entry_date DESC LIMIT 1), weight) WHERE id = $1")
+        .bind(user_id)
+        .execute(tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Logs the weight of a user on a date (replacing any previous entry on the same date)
+pub async fn add_weight_entry(
+    user_id: i32,
+    entry_date: NaiveDate,
+    weight: f64,
+    dbpool: &PgPool,
+) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("INSERT INTO weight_entry(user_id, entry_date, weight) VALUES ($1, $2, $3) ON CONFLICT (user_id, entry_date) DO UPDATE SET weight = EXCLUDED.weight")
+        .bind(user_id)
+        .bind(entry_date)
+        .bind(weight)
+        .execute(&mut tx)
+        .await?;
+
+    sync_user_weight(user_id, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_weight_entry(weight_entry: &WeightEntry, dbpool: &PgPool) -> Result<()> {
+    let mut tx = dbpool.begin().await?;
+
+    sqlx::query("DELETE FROM weight_entry WHERE id = $1")
+        .bind(weight_entry.id)
+        .execute(&mut tx)
+        .await?;
+
+    sync_user_weight(weight_entry.user_id, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Writes a batch of foods into the public catalog, in a single transaction.
+/// Foods are matched by their FDC ID, so existing foods are updated instead of duplicated.
+pub async fn import_catalog_foods(
+    foods: &[CatalogFood],
+    nutrient_ids: &HashMap<String, i32>,
+    dbpool: &PgPool,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut tx = dbpool.begin().await?;
+
+    let names: Vec<&str> = foods.iter().map(|food| food.name.as_str()).collect();
+    let fdc_ids: Vec<i32> = foods.iter().map(|food| food.fdc_id).collect();
+    let densities: Vec<Option<f64>> = foods.iter().map(|food| food.density).collect();
+
+    // Catalog foods from before the importer have no FDC ID. Each of them is matched once with the
+    // first imported food of the same name, if it has the same base serving (100 g) and no other
+    // catalog food has this name, so that it is updated instead of duplicated.
+    let matched = sqlx::query("WITH imported AS (SELECT DISTINCT ON (name) name, fdc_id FROM UNNEST($1::VARCHAR[], $2::INTEGER[]) WITH ORDINALITY AS t(name, fdc_id, position) WHERE NOT EXISTS (SELECT 1 FROM food WHERE food.fdc_id = t.fdc_id) ORDER BY name, position), legacy AS (SELECT MIN(food.id) AS id, food.name FROM food WHERE food.user_id = $3 AND food.fdc_id IS NULL AND EXISTS (SELECT 1 FROM serving WHERE serving.food_id = food.id AND serving.relative IS NULL AND serving.unit = 'g' AND serving.amount = $4) GROUP BY food.name HAVING COUNT(*) = 1) UPDATE food SET fdc_id = imported.fdc_id FROM legacy JOIN imported ON imported.name = legacy.name WHERE food.id = legacy.id")
+        .bind(&names)
+        .bind(&fdc_ids)
+        .bind(CATALOG_USER_ID)
+        .bind(USDA_BASE_SERVING)
+        .execute(&mut tx)
+        .await?;
+
+    summary.foods_matched = matched.rows_affected() as usize;
+
+    // 'xmax = 0' only holds for rows that were inserted (not updated) by this statement
+    let rows = sqlx::query("INSERT INTO food(name, user_id, fdc_id, density) SELECT name, $4, fdc_id, density FROM UNNEST($1::VARCHAR[], $2::INTEGER[], $3::FLOAT[]) AS t(name, fdc_id, density) ON CONFLICT (fdc_id) DO UPDATE SET name = EXCLUDED.name, density = EXCLUDED.density RETURNING id, fdc_id, (xmax = 0) AS inserted")
+        .bind(&names)
+        .bind(&fdc_ids)
+        .bind(&densities)
+        .bind(CATALOG_USER_ID)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let mut food_ids: HashMap<i32, i32> = HashMap::new();
+    for row in rows {
+        let inserted: bool = row.try_get("inserted")?;
+        if inserted {
+            summary.foods_inserted += 1;
+        } else {
+            summary.foods_updated += 1;
+        }
+
+        food_ids.insert(row.try_get("fdc_id")?, row.try_get("id")?);
     }
 
-    Some(())
+    // Reuse the base serving of the foods that were already imported (or matched)
+    let all_food_ids: Vec<i32> = food_ids.values().copied().collect();
+    let rows = sqlx::query("SELECT DISTINCT ON (food_id) food_id, id FROM serving WHERE food_id = ANY($1) AND relative IS NULL ORDER BY food_id, (unit = 'g' AND amount = $2) DESC, id")
+        .bind(&all_food_ids)
+        .bind(USDA_BASE_SERVING)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let mut base_serving_ids: HashMap<i32, i32> = HashMap::new();
+    for row in rows {
+        base_serving_ids.insert(row.try_get("food_id")?, row.try_get("id")?);
+    }
+
+    let new_serving_food_ids: Vec<i32> = all_food_ids
+        .iter()
+        .copied()
+        .filter(|food_id| !base_serving_ids.contains_key(food_id))
+        .collect();
+
+    let rows = sqlx::query("INSERT INTO serving(food_id, unit, amount, relative) SELECT food_id, 'g', $2, NULL FROM UNNEST($1::INTEGER[]) AS t(food_id) RETURNING id, food_id")
+        .bind(&new_serving_food_ids)
+        .bind(USDA_BASE_SERVING)
+        .fetch_all(&mut tx)
+        .await?;
+
+    for row in rows {
+        base_serving_ids.insert(row.try_get("food_id")?, row.try_get("id")?);
+    }
+
+    // Replace the nutrients of the base servings
+    let serving_ids: Vec<i32> = base_serving_ids.values().copied().collect();
+    sqlx::query("DELETE FROM serving_nutrient WHERE serving_id = ANY($1)")
+        .bind(&serving_ids)
+        .execute(&mut tx)
+        .await?;
+
+    let mut nutrient_serving_ids: Vec<i32> = vec![];
+    let mut nutrient_nutrient_ids: Vec<i32> = vec![];
+    let mut nutrient_amounts: Vec<f64> = vec![];
+    for food in foods {
+        let serving_id = base_serving_ids[&food_ids[&food.fdc_id]];
+        for (name, amount) in &food.nutrients {
+            if let Some(nutrient_id) = nutrient_ids.get(name) {
+                nutrient_serving_ids.push(serving_id);
+                nutrient_nutrient_ids.push(*nutrient_id);
+                nutrient_amounts.push(*amount);
+            }
+        }
+    }
+
+    sqlx::query("INSERT INTO serving_nutrient(serving_id, nutrient_id, amount) SELECT * FROM UNNEST($1::INTEGER[], $2::INTEGER[], $3::FLOAT[])")
+        .bind(&nutrient_serving_ids)
+        .bind(&nutrient_nutrient_ids)
+        .bind(&nutrient_amounts)
+        .execute(&mut tx)
+        .await?;
+
+    summary.nutrients = nutrient_amounts.len();
+
+    // Portions become servings relative to the base serving. They are matched by unit,
+    // since servings that are in use (e.g in meals) can't be replaced.
+    let rows = sqlx::query("SELECT id, food_id, unit FROM serving WHERE food_id = ANY($1) AND relative IS NOT NULL")
+        .bind(&all_food_ids)
+        .fetch_all(&mut tx)
+        .await?;
+
+    let mut portion_serving_ids: HashMap<(i32, String), i32> = HashMap::new();
+    for row in rows {
+        portion_serving_ids.insert((row.try_get("food_id")?, row.try_get("unit")?), row.try_get("id")?);
+    }
+
+    let mut portion_ids: Vec<Option<i32>> = vec![];
+    let mut portion_food_ids: Vec<i32> = vec![];
+    let mut portion_units: Vec<&str> = vec![];
+    let mut portion_amounts: Vec<f64> = vec![];
+    let mut portion_relatives: Vec<i32> = vec![];
+    for food in foods {
+        let food_id = food_ids[&food.fdc_id];
+        for (unit, grams) in &food.portions {
+            // New portions get their ID from the database
+            portion_ids.push(portion_serving_ids.get(&(food_id, unit.clone())).copied());
+            portion_food_ids.push(food_id);
+            portion_units.push(unit);
+            portion_amounts.push(*grams);
+            portion_relatives.push(base_serving_ids[&food_id]);
+        }
+    }
+
+    sqlx::query("INSERT INTO serving(id, food_id, unit, amount, relative) SELECT COALESCE(id, nextval(pg_get_serial_sequence('serving', 'id'))), food_id, unit, amount, relative FROM UNNEST($1::INTEGER[], $2::INTEGER[], $3::VARCHAR[], $4::FLOAT[], $5::INTEGER[]) AS t(id, food_id, unit, amount, relative) ON CONFLICT (id) DO UPDATE SET amount = EXCLUDED.amount, relative = EXCLUDED.relative")
+        .bind(&portion_ids)
+        .bind(&portion_food_ids)
+        .bind(&portion_units)
+        .bind(&portion_amounts)
+        .bind(&portion_relatives)
+        .execute(&mut tx)
+        .await?;
+
+    summary.portions = portion_ids.len();
+
+    let mut barcode_food_ids: Vec<i32> = vec![];
+    let mut barcode_codes: Vec<&str> = vec![];
+    for food in foods {
+        for code in &food.barcodes {
+            barcode_food_ids.push(food_ids[&food.fdc_id]);
+            barcode_codes.push(code);
+        }
+    }
+
+    sqlx::query("INSERT INTO food_barcode(food_id, code) SELECT * FROM UNNEST($1::INTEGER[], $2::CHAR(14)[]) ON CONFLICT DO NOTHING")
+        .bind(&barcode_food_ids)
+        .bind(&barcode_codes)
+        .execute(&mut tx)
+        .await?;
+
+    summary.barcodes = barcode_codes.len();
+
+    // Keep the recipes that use the imported foods up to date
+    refresh_recipes(&[], &all_food_ids, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(summary)
 }
@@ -1,7 +1,113 @@
 use sha2::{Sha256, Digest};
+use actix_web::web;
+use once_cell::sync::OnceCell;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version
+};
+use crate::settings::PasswordHashSettings;
 
 pub fn sha256str(string : &str) -> String {
     let mut hash = Sha256::new();
     hash.update(string);
     format!("{:x}", hash.finalize())
 }
+
+pub enum PasswordMatch {
+    Valid,
+    /// The password is correct, but the stored hash uses an outdated scheme or cost
+    /// settings, so it should be replaced by a new hash
+    NeedsRehash,
+    Invalid
+}
+
+fn argon2_hasher(settings : &PasswordHashSettings) -> anyhow::Result<Argon2<'static>> {
+    let params = Params::new(settings.memory_cost, settings.time_cost, settings.parallelism, None)
+        .map_err(|e| anyhow::Error::msg(e.to_string()))?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// Hashes a password with Argon2id and a random salt, returning the encoded (PHC) hash string
+pub fn hash_password(password : &str, settings : &PasswordHashSettings) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = argon2_hasher(settings)?
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::Error::msg(e.to_string()))?;
+
+    Ok(hash.to_string())
+}
+
+fn constant_time_eq(a : &[u8], b : &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Verifies a password against a stored hash. Legacy unsalted SHA-256 hashes are still
+/// accepted, but are reported as 'NeedsRehash'.
+pub fn verify_password(password : &str, password_hash : &str, settings : &PasswordHashSettings) -> PasswordMatch {
+    let password_hash = password_hash.trim_end();
+
+    let parsed_hash = match PasswordHash::new(password_hash) {
+        Ok(hash) => hash,
+        Err(_) => {
+            let attempt_hash = sha256str(password);
+            return if constant_time_eq(attempt_hash.as_bytes(), password_hash.as_bytes()) {
+                PasswordMatch::NeedsRehash
+            } else {
+                PasswordMatch::Invalid
+            };
+        }
+    };
+
+    let hasher = match argon2_hasher(settings) {
+        Ok(hasher) => hasher,
+        Err(_) => return PasswordMatch::Invalid
+    };
+
+    if hasher.verify_password(password.as_bytes(), &parsed_hash).is_err() {
+        return PasswordMatch::Invalid;
+    }
+
+    let is_current = parsed_hash.algorithm == Algorithm::Argon2id.ident() &&
+        Params::try_from(&parsed_hash).is_ok_and(|params| {
+            params.m_cost() == settings.memory_cost &&
+                params.t_cost() == settings.time_cost &&
+                params.p_cost() == settings.parallelism
+        });
+
+    if is_current {
+        PasswordMatch::Valid
+    } else {
+        PasswordMatch::NeedsRehash
+    }
+}
+
+/// Same as 'hash_password', but on the blocking thread pool. Hashing takes tens of milliseconds
+/// and megabytes of memory, which would stall every other request of the worker.
+pub async fn hash_password_blocking(password : &str, settings : &PasswordHashSettings) -> anyhow::Result<String> {
+    let (password, settings) = (password.to_string(), settings.clone());
+    web::block(move || hash_password(&password, &settings)).await?
+}
+
+/// Same as 'verify_password', but on the blocking thread pool
+pub async fn verify_password_blocking(password : &str, password_hash : &str, settings : &PasswordHashSettings) -> PasswordMatch {
+    let (password, password_hash, settings) = (password.to_string(), password_hash.to_string(), settings.clone());
+    web::block(move || verify_password(&password, &password_hash, &settings))
+        .await
+        .unwrap_or(PasswordMatch::Invalid)
+}
+
+/// Verifies a password against a hash that is never used by an account, for logins with an unknown
+/// email. It takes as long as a real verification, so the response time doesn't reveal which emails are registered.
+pub async fn verify_dummy_password(password : &str, settings : &PasswordHashSettings) {
+    static DUMMY_HASH : OnceCell<String> = OnceCell::new();
+
+    let (password, settings) = (password.to_string(), settings.clone());
+    web::block(move || {
+        if let Ok(dummy_hash) = DUMMY_HASH.get_or_try_init(|| hash_password("", &settings)) {
+            verify_password(&password, dummy_hash, &settings);
+        }
+    })
+    .await
+    .ok();
+}
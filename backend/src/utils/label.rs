@@ -0,0 +1,211 @@
+use regex::Regex;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use crate::models::Nutrient;
+use crate::utils::units;
+
+/// Names used on nutrition labels for the NutriNow nutrients (compared without case, spaces or punctuation).
+/// Nutrients that are listed with their own name (e.g 'Protein', 'Sodium', 'Vitamin C') don't need an alias.
+pub const LABEL_NUTRIENT_ALIASES : &[(&str, &str)] = &[
+    // Macronutrients
+    ("Total Fat", "Fats"),
+    ("Fat", "Fats"),
+    ("Total Lipid", "Fats"),
+    ("Total Carbohydrate", "Carbohydrates"),
+    ("Carbohydrate", "Carbohydrates"),
+    ("Carbs", "Carbohydrates"),
+    // Carbohydrates
+    ("Total Sugars", "Sugars"),
+    ("Sugar", "Sugars"),
+    ("Dietary Fiber", "Fiber"),
+    ("Dietary Fibre", "Fiber"),
+    ("Fibre", "Fiber"),
+    // Lipids (Fats)
+    ("Saturated", "Saturated Fat"),
+    ("Saturates", "Saturated Fat"),
+    ("Monounsaturated Fat", "Unsaturated Fat"),
+    ("Polyunsaturated Fat", "Unsaturated Fat"),
+    ("Trans", "Trans Fat"),
+    // Vitamins
+    ("Thiamin", "Vitamin B1"),
+    ("Thiamine", "Vitamin B1"),
+    ("Riboflavin", "Vitamin B2"),
+    ("Niacin", "Vitamin B3"),
+    ("Pantothenic Acid", "Vitamin B5"),
+    ("Biotin", "Vitamin B7"),
+    ("Folate", "Vitamin B9"),
+    ("Folic Acid", "Vitamin B9"),
+    ("Cobalamin", "Vitamin B12"),
+];
+
+/// Micrograms per IU, for the vitamins that labels may list in international units
+const IU_MICROGRAMS : &[(&str, f64)] = &[
+    ("Vitamin A", 0.3), // as retinol
+    ("Vitamin D", 0.025),
+];
+
+/// Serving of the draft food when the label has no serving size
+pub const LABEL_DEFAULT_SERVING : (f64, &str) = (100.0, "g");
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LabelNutrient {
+    pub nutrient_id : i32,
+    pub name : String,
+    pub amount : f64,
+    pub unit : String
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LabelServing {
+    pub amount : f64,
+    pub unit : String
+}
+
+/// Nutrition facts read from a label, for a draft food that the user confirms before saving it
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct NutritionLabel {
+    pub serving : Option<LabelServing>,
+    pub nutrients : Vec<LabelNutrient>,
+    pub unmatched : Vec<String> /* lines with an amount that couldn't be mapped to a nutrient */
+}
+
+/// Lowercases a name and removes everything but letters and digits (e.g 'Vitamin B-12' is 'vitaminb12')
+fn normalize_name(name : &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Finds the nutrient of a label name, either by its own name, by an alias or without the plural
+fn match_nutrient<'a>(name : &str, nutrients : &'a [Nutrient]) -> Option<&'a Nutrient> {
+    let name = normalize_name(name);
+    let name = name.strip_prefix("total").filter(|name| !name.is_empty()).unwrap_or(&name);
+    let find = |name : &str| nutrients.iter().find(|nutrient| normalize_name(&nutrient.name) == name);
+
+    if let Some((_, alias)) = LABEL_NUTRIENT_ALIASES.iter().find(|(label_name, _)| {
+        let label_name = normalize_name(label_name);
+        label_name == name || label_name.strip_prefix("total") == Some(name)
+    }) {
+        return find(&normalize_name(alias));
+    }
+
+    find(name).or_else(|| find(&format!("{}s", name))).or_else(|| name.strip_suffix('s').and_then(find))
+}
+
+/// Converts a nutrient amount from the unit on the label to the unit of the nutrient.
+/// A missing unit is taken as the nutrient's unit, and IU are only known for vitamins A and D.
+pub fn convert_nutrient_amount(amount : f64, unit : &str, nutrient : &Nutrient) -> Option<f64> {
+    let unit = unit.trim();
+    if unit.is_empty() || unit.eq_ignore_ascii_case(&nutrient.unit) {
+        return Some(amount);
+    }
+
+    let (amount, unit) = if unit.eq_ignore_ascii_case("iu") {
+        let (_, micrograms) = IU_MICROGRAMS.iter().find(|(name, _)| *name == nutrient.name)?;
+        (amount * micrograms, "µg")
+    } else {
+        (amount, unit)
+    };
+
+    units::convert_nutrient_amount(amount, unit, &nutrient.unit).ok()
+}
+
+/// Parses an amount, which may be a fraction (e.g '2/3')
+fn parse_amount(amount : &str) -> Option<f64> {
+    let amount = amount.trim().replace(',', ".");
+    let value = match amount.split_once('/') {
+        Some((numerator, denominator)) => numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?,
+        None => amount.parse::<f64>().ok()?
+    };
+
+    Some(value).filter(|value| value.is_finite() && *value >= 0.0)
+}
+
+/// Reads a 'Serving size' line (e.g '2/3 cup (55g)'), preferring the weight in parentheses
+fn parse_serving_size(text : &str) -> Option<LabelServing> {
+    static RE_WEIGHT : OnceCell<Regex> = OnceCell::new();
+    static RE_AMOUNT : OnceCell<Regex> = OnceCell::new();
+    let re_weight = RE_WEIGHT.get_or_init(|| Regex::new(r"(?i)\(\s*(?:about\s*)?(\d+(?:[.,]\d+)?)\s*(g|ml)\s*\)").unwrap());
+    let re_amount = RE_AMOUNT.get_or_init(|| Regex::new(r"(\d+(?:[.,]\d+)?(?:\s*/\s*\d+)?)\s*([^\d\s()][^()]*)").unwrap());
+
+    let (amount, unit) = match re_weight.captures(text) {
+        Some(captures) => (captures.get(1)?.as_str(), captures.get(2)?.as_str().to_lowercase()),
+        None => {
+            let captures = re_amount.captures(text)?;
+            (captures.get(1)?.as_str(), captures.get(2)?.as_str().trim().to_string())
+        }
+    };
+
+    let amount = parse_amount(amount).filter(|amount| *amount > 0.0)?;
+    Some(LabelServing { amount, unit })
+}
+
+/// Splits a line into a name, an amount and a unit. The line is either a CSV row ('name,amount,unit')
+/// or label text ('Total Fat 8g 10%', where the percentage of the daily value is ignored). On label
+/// text, the amount must be separated from the name, since names may have digits (e.g 'Vitamin B12').
+fn parse_line(line : &str) -> Option<(String, String, String)> {
+    static RE_LABEL : OnceCell<Regex> = OnceCell::new();
+    let re_label = RE_LABEL.get_or_init(|| {
+        Regex::new(r"^(?P<name>.*?\S)[\s:]+(?:<\s*|less than\s+)?(?P<amount>\d+(?:[.,]\d+)?)\s*(?P<unit>[a-zA-Zµμ]+)?").unwrap()
+    });
+
+    let fields : Vec<&str> = line.split(',').map(|field| field.trim().trim_matches('"').trim()).collect();
+    if fields.len() >= 2 && parse_amount(fields[1]).is_some() {
+        return Some((fields[0].to_string(), fields[1].to_string(), fields.get(2).unwrap_or(&"").to_string()));
+    }
+
+    let captures = re_label.captures(line)?;
+    Some((
+        captures.name("name")?.as_str().trim().to_string(),
+        captures.name("amount")?.as_str().to_string(),
+        captures.name("unit").map_or("", |unit| unit.as_str()).to_string()
+    ))
+}
+
+/// Reads the serving size and nutrient amounts of a nutrition facts panel (pasted label text, or
+/// CSV rows of name, amount and unit), converted to the units of 'nutrients'. Nutrients listed more
+/// than once (e.g monounsaturated and polyunsaturated fats) are summed.
+pub fn parse_nutrition_label(text : &str, nutrients : &[Nutrient]) -> NutritionLabel {
+    let mut label = NutritionLabel::default();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(serving_size) = line.to_lowercase().strip_prefix("serving size") {
+            if label.serving.is_none() {
+                label.serving = parse_serving_size(serving_size);
+            }
+            continue;
+        }
+
+        // Headers (e.g 'Nutrition Facts' or a CSV header) have no amounts
+        let (name, amount, unit) = match parse_line(line) {
+            Some(parts) => parts,
+            None => continue
+        };
+
+        let converted = match_nutrient(&name, nutrients).and_then(|nutrient| {
+            let amount = convert_nutrient_amount(parse_amount(&amount)?, &unit, nutrient)?;
+            Some((nutrient, amount))
+        });
+
+        let (nutrient, amount) = match converted {
+            Some(converted) => converted,
+            None => {
+                label.unmatched.push(line.to_string());
+                continue;
+            }
+        };
+
+        match label.nutrients.iter_mut().find(|label_nutrient| label_nutrient.nutrient_id == nutrient.id) {
+            Some(label_nutrient) => label_nutrient.amount += amount,
+            None => label.nutrients.push(LabelNutrient {
+                nutrient_id: nutrient.id,
+                name: nutrient.name.clone(),
+                amount,
+                unit: nutrient.unit.clone()
+            })
+        }
+    }
+
+    label
+}
@@ -1,5 +1,12 @@
+pub mod auth;
+pub mod barcode;
 pub mod database;
 pub mod hash;
+pub mod label;
+pub mod nutrition;
 pub mod request;
 pub mod time;
+pub mod units;
+pub mod usda;
 pub mod validation;
+pub mod weight;
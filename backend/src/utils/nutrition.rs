@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::{
+    models::Nutrient,
+    routes::meals::{MealInfoFood, MealInfoNutrient}
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NutrientStatus {
+    Below,
+    Within,
+    Above
+}
+
+pub fn calculate_calories(protein : f64, carbohydrates : f64, fats : f64) -> f64 {
+    protein * 4.0 + carbohydrates * 4.0 + fats * 9.0
+}
+
+/// Sums the nutrients of the foods, proportionally to the amount of each serving.
+/// Every nutrient in 'nutrients' is listed (even if no food has it), after the
+/// calculated 'Calories'.
+pub fn sum_food_nutrients<'a, I>(foods : I, nutrients : &[Nutrient]) -> Vec<MealInfoNutrient>
+where
+    I: IntoIterator<Item = &'a MealInfoFood>
+{
+    let mut totals : HashMap<&str, f64> = HashMap::new();
+    for food in foods {
+        for nutrient in &food.base_nutrients {
+            let amount = nutrient.amount / food.serving_base * food.serving_amount;
+            *totals.entry(nutrient.name.as_str()).or_insert(0.0) += amount;
+        }
+    }
+
+    let total = |name : &str| *totals.get(name).unwrap_or(&0.0);
+    let calories = calculate_calories(total("Protein"), total("Carbohydrates"), total("Fats"));
+
+    let mut nutrient_totals = vec![MealInfoNutrient { name: "Calories".to_string(), amount: calories, unit: "kcal".to_string() }];
+    nutrient_totals.extend(nutrients.iter().map(|nutrient| MealInfoNutrient {
+        name: nutrient.name.clone(),
+        amount: total(&nutrient.name),
+        unit: nutrient.unit.clone()
+    }));
+
+    nutrient_totals
+}
+
+/// Converts an intake target to an absolute amount ('relative' targets are per kg of body weight)
+pub fn resolve_intake(intake : Option<f64>, relative : bool, weight : f64) -> Option<f64> {
+    intake.map(|amount| if relative { amount * weight } else { amount })
+}
+
+pub fn nutrient_status(amount : f64, min_intake : Option<f64>, max_intake : Option<f64>) -> NutrientStatus {
+    if min_intake.is_some_and(|min| amount < min) {
+        NutrientStatus::Below
+    } else if max_intake.is_some_and(|max| amount > max) {
+        NutrientStatus::Above
+    } else {
+        NutrientStatus::Within
+    }
+}
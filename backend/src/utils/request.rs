@@ -1,24 +1,92 @@
 use actix_web::{
-    cookie::Cookie,
-    HttpRequest, HttpResponse
+    cookie::{time::Duration, Cookie, SameSite},
+    dev::ServiceResponse,
+    web, HttpMessage, HttpRequest, HttpResponse
 };
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::str::FromStr;
-use crate::utils::database::get_session_user_id;
-use sqlx::PgPool;
+use crate::settings::{Settings, SessionSettings};
 
-fn create_session_removal_cookie<'a>() -> Cookie<'a> {
-    Cookie::build("session_id", "")
+/// Cookie readable by the frontend, which tells if the user is logged in (the
+/// 'session_id' cookie itself is normally HttpOnly)
+const LOGGED_IN_COOKIE : &str = "logged_in";
+
+/// Stored in the request extensions when a request renews its session, so that the
+/// session cookies of the response can follow the new expiry date
+#[derive(Debug, Clone, Copy)]
+pub struct SessionRenewal {
+    pub session_id : Uuid,
+    pub expiry_date : DateTime<Utc>
+}
+
+pub fn session_settings(req : &HttpRequest) -> SessionSettings {
+    req.app_data::<web::Data<Settings>>()
+        .map(|settings| settings.session.clone())
+        .unwrap_or_default()
+}
+
+fn create_session_cookie<'a>(name : &'a str, value : String, http_only : bool, max_age : Duration, settings : &SessionSettings) -> Cookie<'a> {
+    let same_site = match settings.cookie_same_site.to_lowercase().as_str() {
+        "strict" => SameSite::Strict,
+        "none" => SameSite::None,
+        _ => SameSite::Lax
+    };
+
+    Cookie::build(name, value)
         .path("/")
+        .http_only(http_only)
+        .secure(settings.cookie_secure || same_site == SameSite::None) /* browsers drop SameSite=None cookies without Secure */
+        .same_site(same_site)
+        .max_age(max_age)
         .finish()
 }
 
+/// Lifetime of a new session, which is the idle expiry unless the maximum duration is shorter
+pub fn new_session_max_age(settings : &SessionSettings) -> Duration {
+    Duration::hours(settings.duration_hours.min(settings.max_duration_hours).into())
+}
+
+/// Adds the session cookies, which expire along with the session after 'max_age'
+pub fn add_session_cookies<T>(resp : &mut HttpResponse<T>, session_id : &Uuid, max_age : Duration, settings : &SessionSettings) {
+    let session_cookie = create_session_cookie("session_id", session_id.to_string(), settings.cookie_http_only, max_age, settings);
+    let logged_in_cookie = create_session_cookie(LOGGED_IN_COOKIE, "1".to_string(), false, max_age, settings);
+
+    resp.add_cookie(&session_cookie).ok();
+    resp.add_cookie(&logged_in_cookie).ok();
+}
+
+/// Re-issues the session cookies of a request that renewed its session, unless the response
+/// already sets them (e.g when logging out)
+pub fn refresh_session_cookies<B>(res : &mut ServiceResponse<B>) {
+    let renewal = match res.request().extensions().get::<SessionRenewal>() {
+        Some(renewal) => *renewal,
+        None => return
+    };
+
+    if res.response().cookies().any(|cookie| cookie.name() == "session_id") {
+        return;
+    }
+
+    let max_age = Duration::seconds((renewal.expiry_date - Utc::now()).num_seconds().max(0));
+    let settings = session_settings(res.request());
+    add_session_cookies(res.response_mut(), &renewal.session_id, max_age, &settings);
+}
+
+pub fn add_session_removal_cookies<T>(resp : &mut HttpResponse<T>) {
+    for name in ["session_id", LOGGED_IN_COOKIE] {
+        let removal_cookie = Cookie::build(name, "")
+            .path("/")
+            .finish();
+        resp.add_removal_cookie(&removal_cookie).ok();
+    }
+}
+
 pub fn get_session_id<T>(req : &HttpRequest, resp : &mut HttpResponse<T>) -> Option<Uuid> {
-    let removal_cookie = create_session_removal_cookie();
     let cookie = match req.cookie("session_id") {
         Some(c) => c,
         None => {
-            resp.add_removal_cookie(&removal_cookie).ok();
+            add_session_removal_cookies(resp);
             return None;
         }
     };
@@ -26,21 +94,8 @@ pub fn get_session_id<T>(req : &HttpRequest, resp : &mut HttpResponse<T>) -> Opt
     match Uuid::from_str(cookie.value()) {
         Ok(id) => Some(id),
         Err(_) => {
-            resp.add_removal_cookie(&removal_cookie).ok();
+            add_session_removal_cookies(resp);
             None
         }
     }
 }
-
-pub async fn get_user_id<T>(req : &HttpRequest, resp : &mut HttpResponse<T>, dbpool : &PgPool) -> Option<i32> {
-    let removal_cookie = create_session_removal_cookie();
-    let session_id = get_session_id::<T>(req, resp)?;
-
-    match get_session_user_id(&session_id, dbpool).await {
-        None => {
-            resp.add_removal_cookie(&removal_cookie).ok();
-            None
-        },
-        some_id => some_id
-    }
-}
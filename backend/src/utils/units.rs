@@ -0,0 +1,112 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Mass,
+    Volume
+}
+
+/// Known units, with their size in grams (mass) or milliliters (volume). US customary units are used for
+/// the cup and the spoons, since they are the ones found on nutrition labels and on USDA portions.
+const UNITS : &[(&[&str], Dimension, f64)] = &[
+    (&["µg", "μg", "mcg", "ug", "microgram", "micrograms"], Dimension::Mass, 1e-6),
+    (&["mg", "milligram", "milligrams"], Dimension::Mass, 1e-3),
+    (&["g", "gram", "grams"], Dimension::Mass, 1.0),
+    (&["kg", "kilogram", "kilograms"], Dimension::Mass, 1000.0),
+    (&["oz", "ounce", "ounces"], Dimension::Mass, 28.349523125),
+    (&["lb", "lbs", "pound", "pounds"], Dimension::Mass, 453.59237),
+    (&["ml", "milliliter", "milliliters", "millilitre", "millilitres"], Dimension::Volume, 1.0),
+    (&["l", "liter", "liters", "litre", "litres"], Dimension::Volume, 1000.0),
+    (&["cup", "cups"], Dimension::Volume, 236.5882365),
+    (&["tbsp", "tablespoon", "tablespoons"], Dimension::Volume, 14.78676478125),
+    (&["tsp", "teaspoon", "teaspoons"], Dimension::Volume, 4.92892159375)
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub dimension : Dimension,
+    pub size : f64 /* in grams or milliliters */
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitError {
+    Unknown(String),
+    Mismatch(String, String),
+    MissingDensity(String, String)
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnitError::Unknown(unit) => write!(f, "unknown unit '{}'", unit),
+            UnitError::Mismatch(from, to) => write!(f, "'{}' can't be converted to '{}'", from, to),
+            UnitError::MissingDensity(from, to) => write!(f, "'{}' can't be converted to '{}' without the density of the food", from, to)
+        }
+    }
+}
+
+impl std::error::Error for UnitError {}
+
+/// Finds a unit by name, ignoring the case and any description after it (e.g 'cup (chopped)' or 'cup, diced' is a cup)
+pub fn parse_unit(unit : &str) -> Option<Unit> {
+    let name = unit.split(['(', ',']).next()?.trim().trim_end_matches('.').to_lowercase();
+    UNITS.iter()
+        .find(|(names, _, _)| names.contains(&name.as_str()))
+        .map(|(_, dimension, size)| Unit { dimension: *dimension, size: *size })
+}
+
+/// Converts an amount between two units. Units that are not known can only be converted to themselves
+/// (e.g 'slice'), and mass can only be converted to volume with the density of the food (in g/ml).
+pub fn convert_amount(amount : f64, from : &str, to : &str, density : Option<f64>) -> Result<f64, UnitError> {
+    if from.trim().eq_ignore_ascii_case(to.trim()) {
+        return Ok(amount);
+    }
+
+    let from_unit = parse_unit(from).ok_or_else(|| UnitError::Unknown(from.to_string()))?;
+    let to_unit = parse_unit(to).ok_or_else(|| UnitError::Mismatch(from.to_string(), to.to_string()))?;
+
+    let amount = amount * from_unit.size;
+    let amount = match (from_unit.dimension, to_unit.dimension, density) {
+        (from_dimension, to_dimension, _) if from_dimension == to_dimension => amount,
+        (Dimension::Mass, Dimension::Volume, Some(density)) => amount / density,
+        (Dimension::Volume, Dimension::Mass, Some(density)) => amount * density,
+        _ => return Err(UnitError::MissingDensity(from.to_string(), to.to_string()))
+    };
+
+    Ok(amount / to_unit.size)
+}
+
+/// Converts a nutrient amount between units of mass (e.g 'g', 'mg' and 'µg')
+pub fn convert_nutrient_amount(amount : f64, from : &str, to : &str) -> Result<f64, UnitError> {
+    let is_mass = |unit : &str| parse_unit(unit).is_some_and(|unit| unit.dimension == Dimension::Mass);
+    if !from.trim().eq_ignore_ascii_case(to.trim()) && (!is_mass(from) || !is_mass(to)) {
+        return Err(UnitError::Mismatch(from.to_string(), to.to_string()));
+    }
+
+    convert_amount(amount, from, to, None)
+}
+
+/// Calculates the density (in g/ml) of a food from the weight of a volume (e.g 1 cup = 240g)
+pub fn density_from_portion(unit : &str, grams : f64) -> Option<f64> {
+    let unit = parse_unit(unit).filter(|unit| unit.dimension == Dimension::Volume)?;
+    Some(grams / unit.size).filter(|density| density.is_finite() && *density > 0.0)
+}
+
+/// Unit in which the amount of a serving is counted (e.g in meals, diary entries or recipes)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServingUnit {
+    pub unit : String,
+    pub base : Option<(String, f64)>, /* for relative servings: unit of the base serving, amount of it in one unit */
+    pub density : Option<f64> /* g/ml of the food, when it's known */
+}
+
+/// Converts an amount to the unit of a serving. Relative servings (e.g 'slice') also accept
+/// any unit compatible with their base serving (e.g 56g are 2 slices of 28g).
+pub fn convert_serving_amount(amount : f64, unit : &str, serving : &ServingUnit) -> Result<f64, UnitError> {
+    match &serving.base {
+        Some((base_unit, base_amount)) if !unit.trim().eq_ignore_ascii_case(serving.unit.trim()) => {
+            Ok(convert_amount(amount, unit, base_unit, serving.density)? / base_amount)
+        },
+        _ => convert_amount(amount, unit, &serving.unit, serving.density)
+    }
+}
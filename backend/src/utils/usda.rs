@@ -0,0 +1,317 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::{Duration, Instant}
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use crate::utils::{
+    barcode::normalize_barcode,
+    database::{fetch_nutrients, import_catalog_foods},
+    units::density_from_portion
+};
+
+/// Conversion table from USDA nutrient to NutriNow nutrient
+/// NOTE: NutriNow must use the same units as USDA (or convert between the units)
+pub const USDA_NUTRIENTS : &[(&str, &str)] = &[
+    // Macronutrients
+    ("Protein", "Protein"),
+    ("Carbohydrate, by difference", "Carbohydrates"), // Total carbohydrates, including fiber and sugars
+    ("Total lipid (fat)", "Fats"), // Total fats, including satured, unsaturated and trans fats
+    // Carbohydrates
+    ("Sugars, Total", "Sugars"),
+    ("Fiber, total dietary", "Fiber"),
+    // Lipids (Fats)
+    ("Fatty acids, total saturated", "Saturated Fat"),
+    ("Fatty acids, total monounsaturated", "Unsaturated Fat"),
+    ("Fatty acids, total polyunsaturated", "Unsaturated Fat"),
+    // Vitamins
+    ("Vitamin A, RAE", "Vitamin A"),
+    ("Thiamin", "Vitamin B1"),
+    ("Riboflavin", "Vitamin B2"),
+    ("Niacin", "Vitamin B3"),
+    ("Pantothenic acid", "Vitamin B5"),
+    ("Vitamin B-6", "Vitamin B6"),
+    ("Biotin", "Vitamin B7"),
+    ("Folate, total", "Vitamin B9"),
+    ("Vitamin B-12", "Vitamin B12"),
+    ("Vitamin C, total ascorbic acid", "Vitamin C"),
+    ("Vitamin D (D2 + D3)", "Vitamin D"),
+    ("Vitamin E (alpha-tocopherol)", "Vitamin E"),
+    ("Vitamin K (phylloquinone)", "Vitamin K"),
+    ("Vitamin K (Dihydrophylloquinone)", "Vitamin K"),
+    ("Vitamin K (Menaquinone-4)", "Vitamin K"),
+    // Minerals
+    ("Calcium, Ca", "Calcium"),
+    ("Iron, Fe", "Iron"),
+    ("Magnesium, Mg", "Magnesium"),
+    ("Phosphorus, P", "Phosphorus"),
+    ("Potassium, K", "Potassium"),
+    ("Sodium, Na", "Sodium"),
+    ("Zinc, Zn", "Zinc"),
+    ("Copper, Cu", "Copper"),
+    ("Manganese, Mn", "Manganese"),
+    ("Selenium, Se", "Selenium"),
+    // Others
+    ("Water", "Water") // water is in grams on USDA data, and in mililiters on NutriNow, but 1g of water = 1ml of water
+    // NOTE: Calories are calculated from the macronutrients, so 'Energy' is not imported
+];
+
+/// Amount (in grams) of the base serving of every USDA food, which the nutrient amounts refer to
+pub const USDA_BASE_SERVING : f64 = 100.0;
+
+/// Length of 'food.name', longer descriptions (e.g of branded foods) are cut
+const FOOD_NAME_MAX_LENGTH : usize = 255;
+
+/// Length of 'serving.unit'
+const SERVING_UNIT_MAX_LENGTH : usize = 100;
+
+/// Amount of foods written to the database per transaction
+const IMPORT_BATCH_SIZE : usize = 1000;
+
+#[derive(Deserialize, Debug)]
+pub struct FdcNutrient {
+    pub name : String
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FdcFoodNutrient {
+    #[serde(rename = "type")]
+    pub kind : String,
+    pub nutrient : Option<FdcNutrient>,
+    pub amount : Option<f64>
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FdcMeasureUnit {
+    pub abbreviation : Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FdcFoodPortion {
+    pub amount : Option<f64>,
+    pub gram_weight : Option<f64>,
+    pub modifier : Option<String>,
+    pub portion_description : Option<String>,
+    pub measure_unit : Option<FdcMeasureUnit>
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FdcFood {
+    pub fdc_id : i32,
+    pub description : String,
+    #[serde(default)]
+    pub food_nutrients : Vec<FdcFoodNutrient>,
+    #[serde(default)]
+    pub food_portions : Vec<FdcFoodPortion>,
+    pub gtin_upc : Option<String> /* only on branded foods */
+}
+
+/// A food of the public catalog, ready to be written to the database
+#[derive(Debug, PartialEq)]
+pub struct CatalogFood {
+    pub fdc_id : i32,
+    pub name : String,
+    pub nutrients : Vec<(String, f64)>, /* NutriNow nutrient name, amount in the base serving */
+    pub portions : Vec<(String, f64)>, /* unit (e.g 'cup' or 'large'), grams in one unit */
+    pub density : Option<f64>, /* g/ml, from the first portion with a unit of volume */
+    pub barcodes : Vec<String> /* normalized */
+}
+
+#[derive(Default, Debug)]
+pub struct ImportSummary {
+    pub files : usize,
+    pub foods_read : usize,
+    pub foods_inserted : usize,
+    pub foods_updated : usize,
+    pub foods_matched : usize, /* catalog foods without FDC ID, matched by name (and also counted as updated) */
+    pub nutrients : usize,
+    pub portions : usize,
+    pub barcodes : usize,
+    pub elapsed : Duration
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Files read: {}", self.files)?;
+        writeln!(f, "Foods read: {}", self.foods_read)?;
+        writeln!(f, "Foods inserted: {}", self.foods_inserted)?;
+        writeln!(f, "Foods updated: {}", self.foods_updated)?;
+        writeln!(f, "Foods matched by name: {}", self.foods_matched)?;
+        writeln!(f, "Nutrient amounts written: {}", self.nutrients)?;
+        writeln!(f, "Portions written: {}", self.portions)?;
+        writeln!(f, "Barcodes written: {}", self.barcodes)?;
+        write!(f, "Elapsed time: {:.1}s", self.elapsed.as_secs_f64())
+    }
+}
+
+/// Reads a FoodData Central JSON file (e.g '{ "FoundationFoods": [...] }')
+pub fn read_fdc_foods<R : Read>(reader : R) -> anyhow::Result<Vec<FdcFood>> {
+    let datasets : HashMap<String, Vec<FdcFood>> = serde_json::from_reader(reader)?;
+    Ok(datasets.into_values().flatten().collect())
+}
+
+pub fn convert_fdc_food(fdc_food : &FdcFood) -> CatalogFood {
+    let mut nutrients : Vec<(String, f64)> = vec![];
+
+    for food_nutrient in &fdc_food.food_nutrients {
+        // ignore entries that are not nutrients, or that have no amount
+        if food_nutrient.kind != "FoodNutrient" {
+            continue;
+        }
+
+        let (nutrient, amount) = match (&food_nutrient.nutrient, food_nutrient.amount) {
+            (Some(nutrient), Some(amount)) => (nutrient, amount),
+            _ => continue
+        };
+
+        // ignore nutrients that are not in the database
+        let nutrient_name = match USDA_NUTRIENTS.iter().find(|(usda_name, _)| *usda_name == nutrient.name) {
+            Some((_, name)) => name.to_string(),
+            None => continue
+        };
+
+        // account for summed nutrients (e.g unsatured fats)
+        match nutrients.iter_mut().find(|(name, _)| *name == nutrient_name) {
+            Some((_, total)) => *total += amount,
+            None => nutrients.push((nutrient_name, amount))
+        }
+    }
+
+    let mut portions : Vec<(String, f64)> = vec![];
+    for portion in &fdc_food.food_portions {
+        if let Some((unit, grams)) = convert_fdc_portion(portion) {
+            // the first portion with a certain unit wins
+            if !portions.iter().any(|(portion_unit, _)| *portion_unit == unit) {
+                portions.push((unit, grams));
+            }
+        }
+    }
+
+    let density = portions.iter().find_map(|(unit, grams)| density_from_portion(unit, *grams));
+
+    CatalogFood {
+        fdc_id: fdc_food.fdc_id,
+        name: fdc_food.description.chars().take(FOOD_NAME_MAX_LENGTH).collect(),
+        nutrients,
+        portions,
+        density,
+        // invalid barcodes (e.g wrong check digit) are skipped
+        barcodes: fdc_food.gtin_upc.as_deref().and_then(normalize_barcode).into_iter().collect()
+    }
+}
+
+/// Converts a food portion (e.g '2 slices = 56g') into a relative serving unit and its weight in grams (e.g 'slice', 28)
+pub fn convert_fdc_portion(portion : &FdcFoodPortion) -> Option<(String, f64)> {
+    let gram_weight = portion.gram_weight.filter(|grams| grams.is_finite() && *grams > 0.0)?;
+    let amount = portion.amount.filter(|amount| amount.is_finite() && *amount > 0.0).unwrap_or(1.0);
+
+    // Some datasets use numeric codes as modifiers, which are not useful to the user
+    let modifier = portion.modifier
+        .as_deref()
+        .map(str::trim)
+        .filter(|modifier| !modifier.is_empty() && !modifier.chars().all(|c| c.is_ascii_digit()));
+
+    let abbreviation = portion.measure_unit
+        .as_ref()
+        .and_then(|unit| unit.abbreviation.as_deref())
+        .filter(|abbreviation| *abbreviation != "undetermined");
+
+    let description = portion.portion_description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty() && *description != "Quantity not specified");
+
+    let unit = match (abbreviation, description, modifier) {
+        (Some(abbreviation), _, Some(modifier)) => format!("{} ({})", abbreviation, modifier),
+        (Some(abbreviation), _, None) => abbreviation.to_string(),
+        // the description already contains the amount (e.g '1 cup')
+        (None, Some(description), _) => description.strip_prefix("1 ").unwrap_or(description).to_string(),
+        (None, None, Some(modifier)) => modifier.to_string(),
+        (None, None, None) => return None
+    };
+
+    Some((unit.chars().take(SERVING_UNIT_MAX_LENGTH).collect(), gram_weight / amount))
+}
+
+/// Lists the files to import ('paths' can contain files and directories)
+fn collect_files(paths : &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files : Vec<PathBuf> = vec![];
+
+    for path in paths {
+        if path.is_dir() {
+            let mut dir_files : Vec<PathBuf> = std::fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file())
+                .collect();
+            dir_files.sort();
+            files.append(&mut dir_files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+fn read_fdc_file(path : &Path) -> anyhow::Result<Vec<FdcFood>> {
+    let file = File::open(path)?;
+    read_fdc_foods(BufReader::new(file))
+}
+
+/// Imports FoodData Central JSON files into the public catalog.
+/// Foods that were already imported (same FDC ID) are updated, and so are the catalog
+/// foods without FDC ID that match an imported food by name (see 'import_catalog_foods').
+pub async fn import_usda(paths : &[PathBuf], dbpool : &PgPool) -> anyhow::Result<ImportSummary> {
+    let start = Instant::now();
+    let mut summary = ImportSummary::default();
+
+    let nutrient_ids : HashMap<String, i32> = fetch_nutrients(dbpool)
+        .await
+        .ok_or_else(|| anyhow::Error::msg("Failed to fetch nutrients"))?
+        .into_iter()
+        .map(|nutrient| (nutrient.name, nutrient.id))
+        .collect();
+
+    for file in collect_files(paths)? {
+        log::info!("Processing: {}", file.display());
+        let fdc_foods = read_fdc_file(&file)
+            .map_err(|e| anyhow::Error::msg(format!("Failed to read '{}': {}", file.display(), e)))?;
+
+        summary.files += 1;
+        summary.foods_read += fdc_foods.len();
+
+        // The same food can't be written twice in a single statement, so keep its last occurrence
+        let mut foods : Vec<CatalogFood> = vec![];
+        let mut food_indices : HashMap<i32, usize> = HashMap::new();
+        for fdc_food in &fdc_foods {
+            let food = convert_fdc_food(fdc_food);
+            match food_indices.get(&food.fdc_id) {
+                Some(&index) => foods[index] = food,
+                None => {
+                    food_indices.insert(food.fdc_id, foods.len());
+                    foods.push(food);
+                }
+            }
+        }
+
+        for batch in foods.chunks(IMPORT_BATCH_SIZE) {
+            let batch_summary = import_catalog_foods(batch, &nutrient_ids, dbpool).await?;
+            summary.foods_inserted += batch_summary.foods_inserted;
+            summary.foods_updated += batch_summary.foods_updated;
+            summary.foods_matched += batch_summary.foods_matched;
+            summary.nutrients += batch_summary.nutrients;
+            summary.portions += batch_summary.portions;
+            summary.barcodes += batch_summary.barcodes;
+        }
+    }
+
+    summary.elapsed = start.elapsed();
+
+    Ok(summary)
+}
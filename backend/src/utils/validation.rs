@@ -1,13 +1,51 @@
 use regex::Regex;
 use once_cell::sync::OnceCell;
 use chrono::NaiveDate;
-use crate::utils::time::has_date_passed;
+use crate::{
+    models::{ApiError, FieldError},
+    utils::time::has_date_passed
+};
 
-pub fn check_name(name : &String) -> bool {
+/// Collects the invalid fields of a request, to reject them all at once
+#[derive(Default)]
+pub struct FieldChecks {
+    errors : Vec<FieldError>
+}
+
+impl FieldChecks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records 'field' as invalid (with 'message') unless 'valid'
+    pub fn check(mut self, valid : bool, field : &str, message : &str) -> Self {
+        if !valid {
+            self.errors.push(FieldError::new(field, message));
+        }
+        self
+    }
+
+    /// Fails with 'ApiError::InvalidFields' if any field is invalid
+    pub fn finish(self) -> Result<(), ApiError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::InvalidFields(self.errors))
+        }
+    }
+}
+
+impl From<Vec<FieldError>> for FieldChecks {
+    fn from(errors : Vec<FieldError>) -> Self {
+        Self { errors }
+    }
+}
+
+pub fn check_name(name : &str) -> bool {
     name.len() <= 100
 }
 
-pub fn check_email(email : &String) -> bool {
+pub fn check_email(email : &str) -> bool {
     static RE : OnceCell<Regex> = OnceCell::new();
     let re = RE.get_or_init(|| {
         Regex::new(r"^\w+([-+.']\w+)*@\w+([-.]\w+)*\.\w+([-.]\w+)*$").unwrap()
@@ -28,7 +66,88 @@ pub fn check_birthdate(date : &NaiveDate) -> bool {
     has_date_passed(date)
 }
 
-pub fn check_password(password : &String) -> bool {
+pub fn check_password(password : &str) -> bool {
     // TODO: Remove this check when the registration no longer stores in the 'credentials' table
     password.len() <= 255
 }
+
+/// Checks the profile of a user account, with a description of each invalid field
+pub fn check_user_profile(name : &str, birthdate : &NaiveDate, email : &str, gender : char, weight : f64) -> Vec<FieldError> {
+    let mut errors = vec![];
+
+    if !check_name(name) {
+        errors.push(FieldError::new("name", "must be at most 100 characters"));
+    }
+    if !check_birthdate(birthdate) {
+        errors.push(FieldError::new("birthdate", "must be in the past"));
+    }
+    if !check_email(email) {
+        errors.push(FieldError::new("email", "must be a valid email address"));
+    }
+    if !check_gender(gender) {
+        errors.push(FieldError::new("gender", "must be 'M' or 'F'"));
+    }
+    if !check_weight(weight) {
+        errors.push(FieldError::new("weight", "must be positive"));
+    }
+
+    errors
+}
+
+/// Checks the intake range of a diet nutrient, with a description of each invalid field
+pub fn check_intake(min_intake : Option<f64>, max_intake : Option<f64>) -> Vec<FieldError> {
+    let non_negative = |intake : Option<f64>| intake.is_none_or(|x| x >= 0.0);
+    let mut errors = vec![];
+
+    if !non_negative(min_intake) {
+        errors.push(FieldError::new("min_intake", "must not be negative"));
+    }
+    if !non_negative(max_intake) {
+        errors.push(FieldError::new("max_intake", "must not be negative"));
+    }
+    if let (Some(min), Some(max)) = (min_intake, max_intake) {
+        if min > max {
+            errors.push(FieldError::new("max_intake", "must not be less than min_intake"));
+        }
+    }
+
+    errors
+}
+
+pub fn check_food_name(name : &str) -> bool {
+    !name.is_empty() && name.len() <= 255
+}
+
+pub fn check_serving_unit(unit : &str) -> bool {
+    !unit.is_empty() && unit.len() <= 100
+}
+
+pub fn check_serving_amount(amount : f64) -> bool {
+    amount.is_finite() && amount > 0.0
+}
+
+pub fn check_nutrient_amount(amount : f64) -> bool {
+    amount.is_finite() && amount >= 0.0
+}
+
+pub fn check_density(density : f64) -> bool {
+    density.is_finite() && density > 0.0
+}
+
+/// Parses a comma separated list of IDs (e.g '3,1,2')
+pub fn parse_id_list(list : &str) -> Option<Vec<i32>> {
+    list.split(',')
+        .filter(|id| !id.trim().is_empty())
+        .map(|id| id.trim().parse().ok())
+        .collect()
+}
+
+/// Checks if 'order' is a reordering of 'ids' (every ID exactly once)
+pub fn check_order(order : &[i32], ids : &[i32]) -> bool {
+    let mut order = order.to_vec();
+    let mut ids = ids.to_vec();
+    order.sort_unstable();
+    ids.sort_unstable();
+
+    order == ids
+}
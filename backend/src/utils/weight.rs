@@ -0,0 +1,41 @@
+use chrono::NaiveDate;
+use crate::models::WeightEntry;
+
+/// Average of the entries within the last 'window_days' (inclusive) of each entry.
+/// The entries must be sorted by date.
+pub fn moving_averages(entries : &[WeightEntry], window_days : i64) -> Vec<f64> {
+    entries
+        .iter()
+        .map(|entry| {
+            let window : Vec<f64> = entries
+                .iter()
+                .filter(|e| e.entry_date <= entry.entry_date && (entry.entry_date - e.entry_date).num_days() < window_days)
+                .map(|e| e.weight)
+                .collect();
+
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
+
+/// Rate of change in kilograms per week, from the least squares fit of the entries.
+/// Needs entries on at least two different dates.
+pub fn weekly_rate(entries : &[WeightEntry]) -> Option<f64> {
+    let first_date : NaiveDate = entries.first()?.entry_date;
+    let points : Vec<(f64, f64)> = entries
+        .iter()
+        .map(|e| ((e.entry_date - first_date).num_days() as f64, e.weight))
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance : f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance : f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    if variance == 0.0 {
+        return None;
+    }
+
+    Some(covariance / variance * 7.0)
+}
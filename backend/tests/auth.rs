@@ -0,0 +1,188 @@
+mod common;
+
+use actix_web::{cookie::{time::Duration, Cookie}, http::StatusCode, test};
+use chrono::NaiveDate;
+use nutrinow::{create_app, settings::Settings, utils::database::*};
+use sqlx::Row;
+
+#[actix_web::test]
+async fn rejects_anonymous_users() {
+    let dbpool = common::connect().await;
+    let app = test::init_service(create_app(dbpool, Settings::load().unwrap())).await;
+    let form = [("meal_id", "1"), ("serving_id", "1"), ("amount", "1")];
+
+    // Without a session, or with one that doesn't exist, the session cookies are cleared too
+    let requests = [
+        test::TestRequest::post().uri("/api/add_meal_serving").set_form(form).to_request(),
+        test::TestRequest::post().uri("/api/add_meal_serving").cookie(Cookie::new("session_id", "invalid")).set_form(form).to_request(),
+        test::TestRequest::post().uri("/api/add_meal_serving").cookie(Cookie::new("session_id", uuid::Uuid::new_v4().to_string())).set_form(form).to_request()
+    ];
+
+    for req in requests {
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.response().cookies().any(|cookie| cookie.name() == "session_id" && cookie.max_age() == Some(Duration::ZERO)));
+    }
+}
+
+#[actix_web::test]
+async fn denies_private_servings_of_other_users() {
+    let dbpool = common::connect().await;
+    let settings = Settings::load().unwrap();
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+    // Everything that the user owns is made with a food of their own
+    let food_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id;
+    let meal_id = create_meal(diet_id, &"Breakfast".to_string(), &dbpool).await.unwrap();
+    add_meal_serving(meal_id, serving_id, 50.0, &dbpool).await.unwrap();
+    let meal_serving_id : i32 = sqlx::query("SELECT id FROM meal_serving WHERE meal_id = $1")
+        .bind(meal_id)
+        .fetch_one(&dbpool)
+        .await
+        .unwrap()
+        .get("id");
+    let diary_entry_id = add_diary_entry(user_id, date, None, serving_id, 50.0, &dbpool).await.unwrap();
+    let recipe_id = create_recipe(user_id, &"Porridge".to_string(), &"portion".to_string(), 1.0, &dbpool).await.unwrap();
+    assert!(add_recipe_ingredient(recipe_id, serving_id, 50.0, &dbpool).await.unwrap());
+    let ingredient_id = fetch_recipe_info(recipe_id, Some(user_id), &dbpool).await.unwrap().ingredients[0].id;
+
+    let other_food_id = create_food(other_user_id, &"Secret sauce".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let other_serving_id = fetch_search_food(other_food_id, Some(other_user_id), &dbpool).await.unwrap().servings[0].id.to_string();
+
+    let session_id = create_session(user_id, None, &settings.session, &dbpool).await.unwrap();
+    let app = test::init_service(create_app(dbpool.clone(), settings)).await;
+
+    let (meal_id, meal_serving_id, diary_entry_id) = (meal_id.to_string(), meal_serving_id.to_string(), diary_entry_id.to_string());
+    let (recipe_id, ingredient_id) = (recipe_id.to_string(), ingredient_id.to_string());
+    let routes : [(&str, Vec<(&str, &str)>); 6] = [
+        ("/api/add_meal_serving", vec![("meal_id", &meal_id), ("serving_id", &other_serving_id), ("amount", "1")]),
+        ("/api/edit_meal_serving", vec![("meal_serving_id", &meal_serving_id), ("serving_id", &other_serving_id), ("amount", "1")]),
+        ("/api/add_diary_entry", vec![("entry_date", "2024-03-01"), ("serving_id", &other_serving_id), ("amount", "1")]),
+        ("/api/edit_diary_entry", vec![("diary_entry_id", &diary_entry_id), ("entry_date", "2024-03-01"), ("serving_id", &other_serving_id), ("amount", "1")]),
+        ("/api/add_recipe_ingredient", vec![("food_id", &recipe_id), ("serving_id", &other_serving_id), ("amount", "1")]),
+        ("/api/edit_recipe_ingredient", vec![("recipe_ingredient_id", &ingredient_id), ("serving_id", &other_serving_id), ("amount", "1")])
+    ];
+
+    for (uri, form) in routes {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .cookie(Cookie::new("session_id", session_id.to_string()))
+            .set_form(form)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", uri);
+    }
+
+    // Nothing was changed
+    let entries = fetch_diary_entries(user_id, date, &dbpool).await.unwrap();
+    assert_eq!((entries.len(), entries[0].serving_id), (1, Some(serving_id)));
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn rejects_login_as_the_catalog_account() {
+    let dbpool = common::connect().await;
+    let app = test::init_service(create_app(dbpool, Settings::load().unwrap())).await;
+
+    let req = test::TestRequest::post().uri("/api/login").set_form([("email", "admin@localhost"), ("password", "nutrinow_admin")]).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(resp.response().cookies().all(|cookie| cookie.name() != "session_id"));
+}
+
+#[actix_web::test]
+async fn makes_same_site_none_cookies_secure() {
+    let settings = nutrinow::settings::SessionSettings { cookie_same_site: "None".to_string(), ..Default::default() };
+    let mut resp = actix_web::HttpResponse::Ok().finish();
+    nutrinow::utils::request::add_session_cookies(&mut resp, &uuid::Uuid::new_v4(), Duration::hours(1), &settings);
+
+    for cookie in resp.cookies() {
+        assert_eq!(cookie.same_site(), Some(actix_web::cookie::SameSite::None));
+        assert_eq!(cookie.secure(), Some(true));
+    }
+    assert_eq!(resp.cookies().count(), 2);
+}
+
+#[actix_web::test]
+async fn rehashes_legacy_passwords_on_login() {
+    let dbpool = common::connect().await;
+    let settings = Settings::load().unwrap();
+    let user_id = common::create_user(&dbpool).await;
+    let email : String = sqlx::query("UPDATE user_account SET password_hash = $1 WHERE id = $2 RETURNING email")
+        .bind(nutrinow::utils::hash::sha256str("test_password"))
+        .bind(user_id)
+        .fetch_one(&dbpool)
+        .await
+        .unwrap()
+        .get("email");
+    let password_hash = || async {
+        sqlx::query("SELECT password_hash FROM user_account WHERE id = $1").bind(user_id).fetch_one(&dbpool).await.unwrap().get::<String, _>("password_hash")
+    };
+    let login = |password : &str| nutrinow::routes::login::LoginForm { email: email.clone(), password: password.to_string() };
+
+    // A wrong password leaves the legacy hash alone
+    let legacy_hash = password_hash().await;
+    assert!(authenticate_user(&login("wrong_password"), None, &settings, &dbpool).await.is_err());
+    assert_eq!(password_hash().await, legacy_hash);
+
+    // The right one replaces it with an Argon2id hash, which keeps working
+    assert!(authenticate_user(&login("test_password"), None, &settings, &dbpool).await.is_ok());
+    assert!(password_hash().await.starts_with("$argon2id$"));
+    assert!(authenticate_user(&login("test_password"), None, &settings, &dbpool).await.is_ok());
+    assert!(authenticate_user(&login("wrong_password"), None, &settings, &dbpool).await.is_err());
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn sets_session_cookie_flags() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let email : String = sqlx::query("SELECT email FROM user_account WHERE id = $1").bind(user_id).fetch_one(&dbpool).await.unwrap().get("email");
+    let mut settings = Settings::load().unwrap();
+    settings.session = nutrinow::settings::SessionSettings {
+        duration_hours: 24,
+        max_duration_hours: 12,
+        cookie_http_only: true,
+        cookie_secure: true,
+        cookie_same_site: "Strict".to_string()
+    };
+    let app = test::init_service(create_app(dbpool.clone(), settings)).await;
+
+    // Only the 'logged_in' cookie is readable by the frontend, and both last as long as the session
+    let req = test::TestRequest::post().uri("/api/login").set_form([("email", email.as_str()), ("password", "test_password")]).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let cookies : Vec<Cookie> = resp.response().cookies().map(|cookie| cookie.into_owned()).collect();
+    assert_eq!(cookies.len(), 2);
+    for cookie in &cookies {
+        assert_eq!(cookie.http_only().unwrap_or(false), cookie.name() == "session_id");
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(actix_web::cookie::SameSite::Strict));
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.max_age(), Some(Duration::hours(12)));
+    }
+
+    // Requests renew the session, and its cookies along with it
+    let session_cookie = cookies.iter().find(|cookie| cookie.name() == "session_id").unwrap().clone();
+    let req = test::TestRequest::get().uri("/api/sessions").cookie(session_cookie.clone()).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let renewed = resp.response().cookies().find(|cookie| cookie.name() == "session_id").unwrap();
+    assert_eq!(renewed.value(), session_cookie.value());
+    assert!(renewed.max_age().is_some_and(|max_age| max_age > Duration::hours(11) && max_age <= Duration::hours(12)));
+
+    // Logging out removes both cookies
+    let req = test::TestRequest::post().uri("/api/logout").cookie(session_cookie).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.response().cookies().filter(|cookie| cookie.max_age() == Some(Duration::ZERO)).count(), 2);
+
+    common::delete_user(user_id, &dbpool).await;
+}
@@ -0,0 +1,22 @@
+use nutrinow::utils::barcode::normalize_barcode;
+
+#[test]
+fn normalizes_barcodes() {
+    // UPC-A, EAN-13, EAN-8 and GTIN-14
+    assert_eq!(normalize_barcode("036000291452").as_deref(), Some("00036000291452"));
+    assert_eq!(normalize_barcode("4006381333931").as_deref(), Some("04006381333931"));
+    assert_eq!(normalize_barcode("96385074").as_deref(), Some("00000096385074"));
+    assert_eq!(normalize_barcode("00036000291452").as_deref(), Some("00036000291452"));
+    // UPC-A and its EAN-13 form are the same product
+    assert_eq!(normalize_barcode("0036000291452"), normalize_barcode("036000291452"));
+    // separators are ignored
+    assert_eq!(normalize_barcode("0 36000 29145 2"), normalize_barcode("036000291452"));
+}
+
+#[test]
+fn rejects_invalid_barcodes() {
+    assert_eq!(normalize_barcode("036000291453"), None); // wrong check digit
+    assert_eq!(normalize_barcode("03600029145"), None); // wrong length
+    assert_eq!(normalize_barcode("03600029145A"), None);
+    assert_eq!(normalize_barcode(""), None);
+}
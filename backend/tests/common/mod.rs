@@ -0,0 +1,70 @@
+// Helpers for the tests that run against the database of the settings ('Config.toml' or 'ConfigDebug.toml')
+#![allow(dead_code)]
+
+use actix_web::{cookie::Cookie, dev::ServiceResponse, http::StatusCode, test};
+use chrono::NaiveDate;
+use nutrinow::{
+    connect_db, migrate_db,
+    routes::register::RegisterForm,
+    settings::{PasswordHashSettings, SessionSettings, Settings},
+    utils::database::{create_session, create_user_account, delete_user_account, fetch_nutrients}
+};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Connects to the database and applies the pending migrations
+pub async fn connect() -> PgPool {
+    let settings = Settings::load().expect("Failed to load settings");
+    let dbpool = connect_db(&settings).await.expect("Failed to connect to database");
+    migrate_db(&dbpool).await.expect("Failed to apply database migrations");
+    dbpool
+}
+
+/// Registers a user with a unique email, returning its ID
+pub async fn create_user(dbpool : &PgPool) -> i32 {
+    let form = RegisterForm {
+        name: "Test".to_string(),
+        birthdate: NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+        email: format!("{}@test.localhost", Uuid::new_v4()),
+        password: "test_password".to_string(),
+        gender: 'M',
+        weight: 70.0
+    };
+
+    create_user_account(&form, &PasswordHashSettings::default(), dbpool).await.expect("Failed to create user");
+    sqlx::query("SELECT id FROM user_account WHERE email = $1")
+        .bind(&form.email)
+        .fetch_one(dbpool)
+        .await
+        .unwrap()
+        .get("id")
+}
+
+/// Deletes a test user, and everything it created
+pub async fn delete_user(user_id : i32, dbpool : &PgPool) {
+    delete_user_account(user_id, dbpool).await.expect("Failed to delete user");
+}
+
+pub async fn nutrient_id(name : &str, dbpool : &PgPool) -> i32 {
+    fetch_nutrients(dbpool).await.unwrap().into_iter().find(|nutrient| nutrient.name == name).unwrap().id
+}
+
+/// Opens a session of the user, returning the value of its 'session_id' cookie
+pub async fn login(user_id : i32, dbpool : &PgPool) -> String {
+    create_session(user_id, None, &SessionSettings::default(), dbpool).await.unwrap().to_string()
+}
+
+/// Form request of a session (see 'login')
+pub fn post_form(uri : &str, session_id : &str, form : &[(&str, &str)]) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri(uri)
+        .cookie(Cookie::new("session_id", session_id.to_string()))
+        .set_form(form)
+}
+
+/// Status and error code of a response ('code' is empty on success)
+pub async fn status_code(resp : ServiceResponse) -> (StatusCode, String) {
+    let status = resp.status();
+    let body : serde_json::Value = test::read_body_json(resp).await;
+    (status, body["code"].as_str().unwrap_or_default().to_string())
+}
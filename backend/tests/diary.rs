@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::NaiveDate;
+use nutrinow::utils::database::*;
+
+#[actix_web::test]
+async fn keeps_diary_history_of_deleted_foods() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await;
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+    let food_id = create_food(user_id, &"Bread".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let base_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    let slice_id = create_serving(food_id, &"slice".to_string(), 28.0, Some(base_id), &dbpool).await.unwrap();
+    set_serving_nutrient(base_id, protein_id, 9.0, &dbpool).await.unwrap();
+
+    add_diary_entry(user_id, date, None, base_id, 50.0, &dbpool).await.unwrap();
+    let slice_entry_id = add_diary_entry(user_id, date, None, slice_id, 2.0, &dbpool).await.unwrap();
+
+    // Deleting a serving keeps the entries that use it (and the servings relative to it)
+    delete_serving(base_id, &dbpool).await.unwrap();
+    let entries = fetch_diary_entries(user_id, date, &dbpool).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|entry| entry.food_id.is_none() && entry.serving_id.is_none() && entry.food_name == "Bread"));
+    assert_eq!((entries[0].serving_base, entries[0].serving_amount, entries[0].serving_unit.as_str()), (100.0, 50.0, "g"));
+    assert_eq!(entries[0].base_nutrients[0].amount, 9.0);
+    // 1 slice = 28 g
+    assert_eq!((entries[1].serving_base, entries[1].serving_unit.as_str()), (1.0, "slice"));
+    assert!((entries[1].base_nutrients[0].amount - 9.0 * 0.28).abs() < 1e-9);
+
+    // Editing an entry replaces the copy with the new serving
+    let other_food_id = create_food(user_id, &"Butter".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let other_base_id = fetch_search_food(other_food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    update_diary_entry(slice_entry_id, date, None, other_base_id, 10.0, &dbpool).await.unwrap();
+    let entries = fetch_diary_entries(user_id, date, &dbpool).await.unwrap();
+    assert_eq!((entries[1].food_id, entries[1].food_name.as_str()), (Some(other_food_id), "Butter"));
+    assert!(entries[1].base_nutrients.is_empty());
+
+    // Same when deleting the whole food
+    delete_food(other_food_id, &dbpool).await.unwrap();
+    let entries = fetch_diary_entries(user_id, date, &dbpool).await.unwrap();
+    assert_eq!((entries[1].food_id, entries[1].food_name.as_str(), entries[1].serving_amount), (None, "Butter", 10.0));
+
+    delete_food(food_id, &dbpool).await.unwrap();
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn copies_diet_to_diary_in_meal_order() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let date = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+
+    let food_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id;
+    let lunch_id = create_meal(diet_id, &"Lunch".to_string(), &dbpool).await.unwrap();
+    let breakfast_id = create_meal(diet_id, &"Breakfast".to_string(), &dbpool).await.unwrap();
+    reorder_meals(diet_id, &[breakfast_id, lunch_id], &dbpool).await.unwrap();
+    add_meal_serving(lunch_id, serving_id, 120.0, &dbpool).await.unwrap();
+    add_meal_serving(breakfast_id, serving_id, 40.0, &dbpool).await.unwrap();
+    add_meal_serving(breakfast_id, serving_id, 20.0, &dbpool).await.unwrap();
+
+    copy_diet_to_diary(user_id, diet_id, date, &dbpool).await.unwrap();
+    let entries = fetch_diary_entries(user_id, date, &dbpool).await.unwrap();
+    let logged : Vec<(Option<&str>, f64)> = entries.iter().map(|entry| (entry.meal_name.as_deref(), entry.serving_amount)).collect();
+    assert_eq!(logged, [(Some("Breakfast"), 40.0), (Some("Breakfast"), 20.0), (Some("Lunch"), 120.0)]);
+
+    common::delete_user(user_id, &dbpool).await;
+}
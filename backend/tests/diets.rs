@@ -0,0 +1,153 @@
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use nutrinow::{create_app, settings::Settings, utils::database::*};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Intake of a nutrient in a '/api/*_diet_nutrient' response, if it's in the diet
+fn intake(body : &serde_json::Value, nutrient_id : i32) -> Option<(serde_json::Value, serde_json::Value, bool)> {
+    body["nutrition"].as_array().unwrap()
+        .iter()
+        .find(|nutrient| nutrient["nutrient_id"] == nutrient_id)
+        .map(|nutrient| (nutrient["min_amount"].clone(), nutrient["max_amount"].clone(), nutrient["relative"].as_bool().unwrap()))
+}
+
+async fn count(query : &str, id : i32, dbpool : &PgPool) -> i64 {
+    sqlx::query(query).bind(id).fetch_one(dbpool).await.unwrap().get(0)
+}
+
+#[actix_web::test]
+async fn adds_edits_and_deletes_diet_nutrients() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let other_session_id = common::login(other_user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id;
+    delete_diet_nutrient(diet_id, protein_id, &dbpool).await.unwrap();
+    let (diet_id, protein) = (diet_id.to_string(), protein_id.to_string());
+
+    let req = common::post_form("/api/add_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein), ("min_intake", "1.6"), ("relative", "true")]);
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    assert_eq!(intake(&body, protein_id), Some((1.6.into(), serde_json::Value::Null, true)));
+
+    let req = common::post_form("/api/edit_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein), ("min_intake", "100"), ("max_intake", "150"), ("relative", "false")]);
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    assert_eq!(intake(&body, protein_id), Some((100.0.into(), 150.0.into(), false)));
+
+    // Only the owner of the diet can change it
+    for uri in ["/api/add_diet_nutrient", "/api/edit_diet_nutrient", "/api/delete_diet_nutrient"] {
+        let req = common::post_form(uri, &other_session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein), ("min_intake", "0"), ("relative", "false")]);
+        assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::FORBIDDEN);
+    }
+
+    let req = common::post_form("/api/delete_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein)]);
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    assert_eq!(intake(&body, protein_id), None);
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn rolls_back_failed_diet_operations() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+
+    // Inserting servings of 12345 g and deleting the diet named 'marker' fail, as the last step of each operation
+    let marker = Uuid::new_v4().to_string();
+    let trigger = format!("fail_{}", marker.replace('-', "_"));
+    sqlx::query(&format!("CREATE FUNCTION {trigger}() RETURNS TRIGGER AS $$ BEGIN RAISE EXCEPTION 'failing on purpose'; END; $$ LANGUAGE plpgsql")).execute(&dbpool).await.unwrap();
+    sqlx::query(&format!("CREATE TRIGGER {trigger} BEFORE INSERT ON meal_serving FOR EACH ROW WHEN (NEW.amount = 12345) EXECUTE FUNCTION {trigger}()")).execute(&dbpool).await.unwrap();
+    sqlx::query(&format!("CREATE TRIGGER {trigger} BEFORE DELETE ON diet FOR EACH ROW WHEN (OLD.name = '{marker}') EXECUTE FUNCTION {trigger}()")).execute(&dbpool).await.unwrap();
+
+    let food_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    create_diet(user_id, &marker, &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id;
+    let meal_id = create_meal(diet_id, &"Breakfast".to_string(), &dbpool).await.unwrap();
+    add_meal_serving(meal_id, serving_id, 50.0, &dbpool).await.unwrap();
+    sqlx::query("UPDATE meal_serving SET amount = 12345 WHERE meal_id = $1").bind(meal_id).execute(&dbpool).await.unwrap();
+
+    // Copying the servings fails, so the copy of the diet and its nutrients are gone too
+    assert!(duplicate_diet(user_id, diet_id, &"Copy".to_string(), &dbpool).await.is_err());
+    assert_eq!(fetch_user_diets(user_id, &dbpool).await.unwrap().len(), 1);
+    assert_eq!(count("SELECT COUNT(*) FROM meal JOIN diet ON diet.id = meal.diet_id WHERE diet.user_id = $1", user_id, &dbpool).await, 1);
+
+    // Deleting the diet itself fails, so its meals, servings and nutrients are still there
+    let nutrients = count("SELECT COUNT(*) FROM diet_nutrition WHERE diet_id = $1", diet_id, &dbpool).await;
+    assert!(nutrients > 0);
+    assert!(delete_diet(diet_id, &dbpool).await.is_err());
+    assert_eq!(count("SELECT COUNT(*) FROM meal_serving WHERE meal_id = $1", meal_id, &dbpool).await, 1);
+    assert_eq!(count("SELECT COUNT(*) FROM meal WHERE diet_id = $1", diet_id, &dbpool).await, 1);
+    assert_eq!(count("SELECT COUNT(*) FROM diet_nutrition WHERE diet_id = $1", diet_id, &dbpool).await, nutrients);
+
+    sqlx::query(&format!("DROP FUNCTION {trigger} CASCADE")).execute(&dbpool).await.unwrap();
+    delete_diet(diet_id, &dbpool).await.unwrap();
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn renames_copies_and_moves_meals() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+    let meal_names = |diet_id : i32| {
+        let dbpool = dbpool.clone();
+        async move { fetch_diet_meals(diet_id, &dbpool).await.unwrap().into_iter().map(|meal| meal.name).collect::<Vec<_>>() }
+    };
+
+    let food_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    create_diet(user_id, &"Other diet".to_string(), &dbpool).await.unwrap();
+    create_diet(other_user_id, &"Not mine".to_string(), &dbpool).await.unwrap();
+    let diets = fetch_user_diets(user_id, &dbpool).await.unwrap();
+    let diet_id = diets.iter().find(|diet| diet.name == "Diet").unwrap().id;
+    let other_diet_id = diets.iter().find(|diet| diet.name == "Other diet").unwrap().id;
+    let foreign_diet_id = fetch_user_diets(other_user_id, &dbpool).await.unwrap()[0].id;
+    let meal_id = create_meal(diet_id, &"Breakfast".to_string(), &dbpool).await.unwrap();
+    create_meal(diet_id, &"Lunch".to_string(), &dbpool).await.unwrap();
+    create_meal(other_diet_id, &"Dinner".to_string(), &dbpool).await.unwrap();
+    add_meal_serving(meal_id, serving_id, 50.0, &dbpool).await.unwrap();
+    let meal = meal_id.to_string();
+
+    let req = common::post_form("/api/edit_meal", &session_id, &[("meal_id", &meal), ("meal_name", "Brunch")]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    let req = common::post_form("/api/edit_meal", &session_id, &[("meal_id", &meal), ("meal_name", "")]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(meal_names(diet_id).await, ["Brunch", "Lunch"]);
+
+    // Copies go at the end of their diet (the same one by default), with the servings of the meal
+    let req = common::post_form("/api/copy_meal", &session_id, &[("meal_id", &meal)]);
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    assert_eq!(body["meal"]["foods"].as_array().unwrap().len(), 1);
+    assert_eq!(meal_names(diet_id).await, ["Brunch", "Lunch", "Brunch"]);
+    let req = common::post_form("/api/copy_meal", &session_id, &[("meal_id", &meal), ("diet_id", &other_diet_id.to_string())]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    assert_eq!(meal_names(other_diet_id).await, ["Dinner", "Brunch"]);
+
+    // Moving a meal to its own diet keeps its place, and it can't go to the diet of another user
+    let req = common::post_form("/api/move_meal", &session_id, &[("meal_id", &meal), ("diet_id", &diet_id.to_string())]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    assert_eq!(meal_names(diet_id).await, ["Brunch", "Lunch", "Brunch"]);
+    assert_eq!(fetch_meal(meal_id, &dbpool).await.unwrap().position, 0);
+    let req = common::post_form("/api/move_meal", &session_id, &[("meal_id", &meal), ("diet_id", &foreign_diet_id.to_string())]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::FORBIDDEN);
+
+    let req = common::post_form("/api/move_meal", &session_id, &[("meal_id", &meal), ("diet_id", &other_diet_id.to_string())]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    assert_eq!(meal_names(diet_id).await, ["Lunch", "Brunch"]);
+    assert_eq!(meal_names(other_diet_id).await, ["Dinner", "Brunch", "Brunch"]);
+    assert_eq!(fetch_meal_info_foods(meal_id, &dbpool).await.unwrap().len(), 1);
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
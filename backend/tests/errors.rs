@@ -0,0 +1,130 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, ResponseError};
+use nutrinow::{create_app, models::ApiError, settings::Settings, utils::database::*};
+use sqlx::Row;
+
+#[actix_web::test]
+async fn answers_client_errors_with_their_status() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await.to_string();
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+
+    let food_id = create_food(user_id, &"Bread".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id.to_string();
+    let other_food_id = create_food(user_id, &"Butter".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let other_serving_id = fetch_search_food(other_food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id.to_string();
+    let food_id = food_id.to_string();
+
+    // Relative to a serving of another food
+    let req = common::post_form("/api/add_food_serving", &session_id, &[("food_id", &food_id), ("unit", "slice"), ("amount", "28"), ("relative", &other_serving_id)]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "invalid_relative_serving".to_string()));
+    let req = common::post_form("/api/edit_food_serving", &session_id, &[("serving_id", &serving_id), ("unit", "g"), ("amount", "100"), ("relative", &other_serving_id)]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "invalid_relative_serving".to_string()));
+
+    // A food that is not a recipe
+    let req = common::post_form("/api/add_recipe_ingredient", &session_id, &[("food_id", &food_id), ("serving_id", &other_serving_id), ("amount", "10")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "not_a_recipe".to_string()));
+    let req = common::post_form("/api/edit_recipe_yield", &session_id, &[("food_id", &food_id), ("yield_unit", "g"), ("yield_amount", "500")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "not_a_recipe".to_string()));
+
+    // Diet nutrients that are already in the diet, that aren't, or that don't exist
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id.to_string();
+    delete_diet_nutrient(diet_id.parse().unwrap(), protein_id.parse().unwrap(), &dbpool).await.unwrap();
+    let req = common::post_form("/api/edit_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein_id), ("min_intake", "50"), ("relative", "false")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::NOT_FOUND, "not_found".to_string()));
+    let req = common::post_form("/api/add_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein_id), ("min_intake", "50"), ("relative", "false")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await.0, StatusCode::OK);
+    let req = common::post_form("/api/add_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein_id), ("min_intake", "50"), ("relative", "false")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::CONFLICT, "diet_nutrient_exists".to_string()));
+    let req = common::post_form("/api/add_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", "-1"), ("min_intake", "50"), ("relative", "false")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::NOT_FOUND, "not_found".to_string()));
+
+    // A session that doesn't exist
+    let req = common::post_form("/api/revoke_session", &session_id, &[("session_id", &uuid::Uuid::new_v4().to_string())]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::NOT_FOUND, "not_found".to_string()));
+
+    // The email of another account
+    let other_email : String = sqlx::query("SELECT email FROM user_account WHERE id = $1").bind(other_user_id).fetch_one(&dbpool).await.unwrap().get("email");
+    let req = common::post_form("/api/edit_user", &session_id, &[("name", "Test"), ("birthdate", "1990-01-01"), ("email", &other_email), ("gender", "M"), ("weight", "70")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::CONFLICT, "email_in_use".to_string()));
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
+
+/// Field names of an 'invalid_input' error
+async fn invalid_fields(resp : actix_web::dev::ServiceResponse) -> Vec<String> {
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body : serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "invalid_input");
+    body["fields"].as_array().unwrap().iter().map(|field| field["field"].as_str().unwrap().to_string()).collect()
+}
+
+#[actix_web::test]
+async fn names_the_invalid_fields() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await.to_string();
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id.to_string();
+
+    let req = common::post_form("/api/add_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein_id), ("min_intake", "-1"), ("relative", "false")]);
+    assert_eq!(invalid_fields(test::call_service(&app, req.to_request()).await).await, ["min_intake"]);
+    let req = common::post_form("/api/edit_diet_nutrient", &session_id, &[("diet_id", &diet_id), ("nutrient_id", &protein_id), ("min_intake", "80"), ("max_intake", "50"), ("relative", "false")]);
+    assert_eq!(invalid_fields(test::call_service(&app, req.to_request()).await).await, ["max_intake"]);
+
+    let req = common::post_form("/api/new_recipe", &session_id, &[("recipe_name", ""), ("yield_unit", "portion"), ("yield_amount", "0")]);
+    assert_eq!(invalid_fields(test::call_service(&app, req.to_request()).await).await, ["recipe_name", "yield_amount"]);
+
+    let req = common::post_form("/api/parse_nutrition_label", &session_id, &[("label", &"a".repeat(10001)), ("food_name", &"a".repeat(256))]);
+    assert_eq!(invalid_fields(test::call_service(&app, req.to_request()).await).await, ["label", "food_name"]);
+
+    let req = common::post_form("/api/change_password", &session_id, &[("current_password", "test_password"), ("new_password", &"a".repeat(256))]);
+    assert_eq!(invalid_fields(test::call_service(&app, req.to_request()).await).await, ["new_password"]);
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn answers_each_error_class_with_its_status_and_code() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+    create_diet(other_user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let other_diet_id = fetch_user_diets(other_user_id, &dbpool).await.unwrap()[0].id.to_string();
+
+    // 401: no session, or wrong credentials
+    let req = test::TestRequest::get().uri("/api/diets");
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNAUTHORIZED, "not_logged_in".to_string()));
+    let req = test::TestRequest::post().uri("/api/login").set_form([("email", "nobody@test.localhost"), ("password", "test_password")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNAUTHORIZED, "auth_failed".to_string()));
+
+    // 403: the resource of another user
+    let req = common::post_form("/api/edit_diet", &session_id, &[("diet_id", &other_diet_id), ("diet_name", "Mine")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::FORBIDDEN, "access_denied".to_string()));
+
+    // 404: a resource that doesn't exist
+    let req = common::post_form("/api/edit_diet", &session_id, &[("diet_id", "-1"), ("diet_name", "Mine")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::NOT_FOUND, "not_found".to_string()));
+
+    // 422: invalid input
+    let req = common::post_form("/api/new_food", &session_id, &[("food_name", ""), ("serving_unit", "g"), ("serving_amount", "100")]);
+    assert_eq!(common::status_code(test::call_service(&app, req.to_request()).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "invalid_input".to_string()));
+
+    // 500: a failure of the database, which the client can only retry
+    let resp = test::TestRequest::default().to_srv_response(ApiError::QueryDiets.error_response());
+    assert_eq!(common::status_code(resp).await, (StatusCode::INTERNAL_SERVER_ERROR, "query_diets".to_string()));
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
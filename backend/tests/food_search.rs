@@ -0,0 +1,46 @@
+mod common;
+
+use nutrinow::{
+    routes::food_search::{FoodOwner, FoodSearchFilters, NutrientFilter},
+    utils::database::*
+};
+
+#[test]
+fn parses_nutrient_filters() {
+    assert_eq!(NutrientFilter::parse_list("1:20:,27::200,3:0.5:1.5"), Some(vec![
+        NutrientFilter { nutrient_id: 1, min: Some(20.0), max: None },
+        NutrientFilter { nutrient_id: 27, min: None, max: Some(200.0) },
+        NutrientFilter { nutrient_id: 3, min: Some(0.5), max: Some(1.5) }
+    ]));
+    assert_eq!(NutrientFilter::parse_list(""), Some(vec![]));
+}
+
+#[test]
+fn rejects_invalid_nutrient_filters() {
+    assert_eq!(NutrientFilter::parse_list("1:20"), None); // missing max
+    assert_eq!(NutrientFilter::parse_list("1:20:30:40"), None);
+    assert_eq!(NutrientFilter::parse_list("x:20:"), None);
+    assert_eq!(NutrientFilter::parse_list("1:abc:"), None);
+    assert_eq!(NutrientFilter::parse_list("1:30:20"), None); // min > max
+}
+
+#[actix_web::test]
+async fn counts_foods_past_the_last_page() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    for name in ["Oats", "Rice", "Lentils"] {
+        create_food(user_id, &name.to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    }
+
+    let filters = FoodSearchFilters { owner: FoodOwner::Mine, ..Default::default() };
+    let (foods, total) = search_foods(None, Some(user_id), &filters, 2, 2, &dbpool).await.unwrap();
+    assert_eq!((foods.len(), total), (1, 3));
+    let (foods, total) = search_foods(None, Some(user_id), &filters, 5, 2, &dbpool).await.unwrap();
+    assert_eq!((foods.len(), total), (0, 3));
+
+    // The offset of the page doesn't fit in an i64
+    assert!(search_foods(None, Some(user_id), &filters, i64::MAX, 2, &dbpool).await.is_none());
+    assert!(search_foods(None, Some(user_id), &filters, 0, 2, &dbpool).await.is_none());
+
+    common::delete_user(user_id, &dbpool).await;
+}
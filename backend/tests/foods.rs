@@ -0,0 +1,190 @@
+mod common;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test::{call_and_read_body_json, call_service, init_service, TestRequest}};
+use nutrinow::{
+    create_app,
+    models::ApiError,
+    settings::Settings,
+    utils::{
+        auth::{AuthenticatedUser, Resource},
+        database::*,
+        validation::*
+    }
+};
+use uuid::Uuid;
+
+#[test]
+fn validates_food_input() {
+    assert!(check_food_name("Overnight oats"));
+    assert!(!check_food_name(""));
+    assert!(!check_food_name(&"a".repeat(256)));
+
+    assert!(check_serving_unit("slice"));
+    assert!(!check_serving_unit(""));
+    assert!(!check_serving_unit(&"a".repeat(101)));
+
+    assert!(check_serving_amount(0.5));
+    assert!(!check_serving_amount(0.0));
+    assert!(!check_serving_amount(f64::NAN));
+
+    assert!(check_nutrient_amount(0.0));
+    assert!(!check_nutrient_amount(-1.0));
+    assert!(!check_nutrient_amount(f64::INFINITY));
+
+    assert!(check_density(1.03));
+    assert!(!check_density(0.0));
+}
+
+#[actix_web::test]
+async fn creates_private_foods() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+
+    let food_id = create_food(user_id, &"Homemade bread".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let food = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap();
+    assert_eq!(food.name, "Homemade bread");
+    assert_eq!(food.servings.len(), 1);
+    assert_eq!((food.servings[0].amount, food.servings[0].unit.as_str(), food.servings[0].relative), (100.0, "g", None));
+
+    // Private foods are only visible to their owner
+    assert!(fetch_search_food(food_id, None, &dbpool).await.is_none());
+    assert!(fetch_search_food(food_id, Some(other_user_id), &dbpool).await.is_none());
+
+    edit_food(food_id, &"White bread".to_string(), Some(0.25), &dbpool).await.unwrap();
+    let food = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap();
+    assert_eq!((food.name.as_str(), food.density), ("White bread", Some(0.25)));
+
+    // Only the owner can change them, and only the owner or anyone (for the catalog) can use them
+    let user = AuthenticatedUser { id: user_id, session_id: Uuid::new_v4() };
+    let other_user = AuthenticatedUser { id: other_user_id, session_id: Uuid::new_v4() };
+    let serving_id = food.servings[0].id;
+    assert!(user.check_owner(Resource::Food(food_id), &dbpool).await.is_ok());
+    assert!(matches!(other_user.check_owner(Resource::Food(food_id), &dbpool).await, Err(ApiError::AccessDenied)));
+    assert!(matches!(other_user.check_access(Resource::Serving(serving_id), &dbpool).await, Err(ApiError::AccessDenied)));
+    assert!(matches!(user.check_owner(Resource::Food(-1), &dbpool).await, Err(ApiError::NotFound)));
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn checks_relative_servings() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+
+    let food_id = create_food(user_id, &"Bread".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let base_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    let other_food_id = create_food(user_id, &"Butter".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let other_base_id = fetch_search_food(other_food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+
+    // 1 slice = 28 g
+    assert!(check_relative_serving(food_id, None, base_id, &dbpool).await);
+    let slice_id = create_serving(food_id, &"slice".to_string(), 28.0, Some(base_id), &dbpool).await.unwrap();
+
+    // Relative to a relative serving
+    assert!(!check_relative_serving(food_id, None, slice_id, &dbpool).await);
+    // Relative to a serving of another food
+    assert!(!check_relative_serving(food_id, None, other_base_id, &dbpool).await);
+    // Relative to itself
+    assert!(!check_relative_serving(food_id, Some(base_id), base_id, &dbpool).await);
+    // Relative to a serving that doesn't exist
+    assert!(!check_relative_serving(food_id, None, -1, &dbpool).await);
+
+    // A serving that others are relative to can't become relative itself
+    let loaf_id = create_serving(food_id, &"loaf".to_string(), 1.0, None, &dbpool).await.unwrap();
+    assert!(!check_relative_serving(food_id, Some(base_id), loaf_id, &dbpool).await);
+    assert!(check_relative_serving(food_id, Some(slice_id), loaf_id, &dbpool).await);
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn edits_serving_nutrients() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await;
+
+    let food_id = create_food(user_id, &"Bread".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let base_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    let slice_id = create_serving(food_id, &"slice".to_string(), 28.0, Some(base_id), &dbpool).await.unwrap();
+
+    set_serving_nutrient(base_id, protein_id, 9.0, &dbpool).await.unwrap();
+    set_serving_nutrient(base_id, protein_id, 8.5, &dbpool).await.unwrap();
+    let food = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap();
+    let nutrients : Vec<(&str, f64)> = food.servings[0].nutrients.iter().map(|nutrient| (nutrient.name.as_str(), nutrient.amount)).collect();
+    assert_eq!(nutrients, vec![("Protein", 8.5)]);
+    // Relative servings take the nutrients of their base serving
+    assert!(food.servings[1].nutrients.is_empty());
+
+    delete_serving_nutrient(base_id, protein_id, &dbpool).await.unwrap();
+    let food = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap();
+    assert!(food.servings[0].nutrients.is_empty());
+
+    // Deleting a serving also deletes the servings relative to it
+    let loaf_id = create_serving(food_id, &"loaf".to_string(), 1.0, None, &dbpool).await.unwrap();
+    delete_serving(base_id, &dbpool).await.unwrap();
+    let serving_ids : Vec<i32> = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings.iter().map(|serving| serving.id).collect();
+    assert_eq!(serving_ids, vec![loaf_id]);
+    assert!(fetch_serving(slice_id, &dbpool).await.is_none());
+
+    delete_food(food_id, &dbpool).await.unwrap();
+    assert!(fetch_search_food(food_id, Some(user_id), &dbpool).await.is_none());
+    assert!(fetch_serving(loaf_id, &dbpool).await.is_none());
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn lists_favorite_and_recent_foods() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let other_user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+    let food_names = |body : serde_json::Value| -> Vec<String> {
+        body["foods"].as_array().unwrap().iter().map(|food| food["name"].as_str().unwrap().to_string()).collect()
+    };
+
+    let oats_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let milk_id = create_food(user_id, &"Milk".to_string(), &"ml".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let secret_id = create_food(other_user_id, &"Secret sauce".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+
+    // Favorites are listed newest first, and starring twice is harmless
+    for food_id in [oats_id, milk_id, milk_id] {
+        let req = common::post_form("/api/add_favorite_food", &session_id, &[("food_id", &food_id.to_string())]);
+        assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    }
+    sqlx::query("UPDATE food_favorite SET created_at = created_at - INTERVAL '1 minute' WHERE food_id = $1").bind(oats_id).execute(&dbpool).await.unwrap();
+    let req = TestRequest::get().uri("/api/favorite_foods").cookie(Cookie::new("session_id", session_id.clone()));
+    assert_eq!(food_names(call_and_read_body_json(&app, req.to_request()).await), ["Milk", "Oats"]);
+
+    // The private foods of other users can't be starred
+    let req = common::post_form("/api/add_favorite_food", &session_id, &[("food_id", &secret_id.to_string())]);
+    assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::FORBIDDEN);
+
+    let req = common::post_form("/api/delete_favorite_food", &session_id, &[("food_id", &milk_id.to_string())]);
+    assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    let req = TestRequest::get().uri("/api/favorite_foods").cookie(Cookie::new("session_id", session_id.clone()));
+    assert_eq!(food_names(call_and_read_body_json(&app, req.to_request()).await), ["Oats"]);
+
+    // Adding servings to meals makes their foods recent, the latest first
+    create_diet(user_id, &"Diet".to_string(), &dbpool).await.unwrap();
+    let diet_id = fetch_user_diets(user_id, &dbpool).await.unwrap()[0].id;
+    let meal_id = create_meal(diet_id, &"Breakfast".to_string(), &dbpool).await.unwrap().to_string();
+    for food_id in [milk_id, oats_id] {
+        let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id.to_string();
+        let req = common::post_form("/api/add_meal_serving", &session_id, &[("meal_id", &meal_id), ("serving_id", &serving_id), ("amount", "100")]);
+        assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+        sqlx::query("UPDATE serving_usage SET last_used = last_used - INTERVAL '1 minute' WHERE user_id = $1").bind(user_id).execute(&dbpool).await.unwrap();
+    }
+    let req = TestRequest::get().uri("/api/recent_foods").cookie(Cookie::new("session_id", session_id.clone()));
+    assert_eq!(food_names(call_and_read_body_json(&app, req.to_request()).await), ["Oats", "Milk"]);
+    let req = TestRequest::get().uri("/api/recent_foods?limit=1").cookie(Cookie::new("session_id", session_id.clone()));
+    assert_eq!(food_names(call_and_read_body_json(&app, req.to_request()).await), ["Oats"]);
+    let req = TestRequest::get().uri("/api/recent_foods?limit=0").cookie(Cookie::new("session_id", session_id.clone()));
+    assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    common::delete_user(user_id, &dbpool).await;
+    common::delete_user(other_user_id, &dbpool).await;
+}
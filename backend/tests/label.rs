@@ -0,0 +1,66 @@
+use nutrinow::{
+    models::Nutrient,
+    utils::label::{parse_nutrition_label, LabelServing}
+};
+
+fn nutrients() -> Vec<Nutrient> {
+    [(1, "Protein", "g"), (2, "Carbohydrates", "g"), (3, "Fats", "g"), (4, "Sugars", "g"), (5, "Fiber", "g"),
+     (6, "Saturated Fat", "g"), (7, "Unsaturated Fat", "g"), (9, "Vitamin A", "μg"), (17, "Vitamin B12", "μg"),
+     (19, "Vitamin D", "μg"), (22, "Calcium", "mg"), (27, "Sodium", "mg"), (31, "Selenium", "mg")]
+        .into_iter()
+        .map(|(id, name, unit)| Nutrient { id, name: name.to_string(), unit: unit.to_string() })
+        .collect()
+}
+
+fn amount_of(label : &nutrinow::utils::label::NutritionLabel, nutrient_id : i32) -> Option<f64> {
+    label.nutrients.iter().find(|nutrient| nutrient.nutrient_id == nutrient_id).map(|nutrient| nutrient.amount)
+}
+
+#[test]
+fn parses_label_text() {
+    let label = parse_nutrition_label("
+        Nutrition Facts
+        8 servings per container
+        Serving size 2/3 cup (55g)
+        Calories 230
+        Total Fat 8g 10%
+          Saturated Fat 1g 5%
+          Polyunsaturated Fat 2g
+          Monounsaturated Fat 3.5g
+        Sodium 0.16g 7%
+        Total Carbohydrate 37g 13%
+          Dietary Fiber 4g 14%
+          Total Sugars 12g
+        Protein 3g
+        Vitamin D 80 IU 10%
+        Vitamin B-12 2.4mcg 100%
+        Calcium 260mg 20%
+        Selenium 20 µg
+    ", &nutrients());
+
+    assert_eq!(label.serving, Some(LabelServing { amount: 55.0, unit: "g".to_string() }));
+    assert_eq!(amount_of(&label, 3), Some(8.0));
+    assert_eq!(amount_of(&label, 6), Some(1.0));
+    assert_eq!(amount_of(&label, 7), Some(5.5)); // summed
+    assert_eq!(amount_of(&label, 27), Some(160.0)); // g -> mg
+    assert_eq!(amount_of(&label, 2), Some(37.0));
+    assert_eq!(amount_of(&label, 5), Some(4.0));
+    assert_eq!(amount_of(&label, 4), Some(12.0));
+    assert_eq!(amount_of(&label, 1), Some(3.0));
+    assert_eq!(amount_of(&label, 19), Some(2.0)); // IU -> μg
+    assert_eq!(amount_of(&label, 17), Some(2.4));
+    assert_eq!(amount_of(&label, 22), Some(260.0));
+    assert!(amount_of(&label, 31).is_some_and(|amount| (amount - 0.02).abs() < 1e-9)); // µg -> mg
+    assert_eq!(label.unmatched, vec!["Calories 230"]);
+}
+
+#[test]
+fn parses_label_csv() {
+    let label = parse_nutrition_label("name,amount,unit\nProtein,12,g\nfat,\"4.5\",g\nVitamin A,1000,IU\nCholesterol,20,mg", &nutrients());
+
+    assert_eq!(label.serving, None);
+    assert_eq!(amount_of(&label, 1), Some(12.0));
+    assert_eq!(amount_of(&label, 3), Some(4.5));
+    assert_eq!(amount_of(&label, 9), Some(300.0));
+    assert_eq!(label.unmatched, vec!["Cholesterol,20,mg"]);
+}
@@ -0,0 +1,39 @@
+use nutrinow::{connect_db, migrate_db, settings::Settings};
+use sqlx::{Executor, Row};
+use uuid::Uuid;
+
+#[actix_web::test]
+async fn migrates_databases_created_from_base_sql() {
+    // A scratch database next to the one of the settings
+    let mut settings = Settings::load().unwrap();
+    let main_dbpool = connect_db(&settings).await.unwrap();
+    let database_name = format!("nutrinow_test_{}", Uuid::new_v4().simple());
+    let (server_url, _) = settings.database_url.rsplit_once('/').unwrap();
+    settings.database_url = format!("{}/{}", server_url, database_name);
+    let dbpool = connect_db(&settings).await.unwrap();
+
+    // The former 'setup/base.sql', run by hand (so there is no record of any migration)
+    dbpool.execute(include_str!("../migrations/0001_baseline.sql")).await.unwrap();
+    sqlx::query("INSERT INTO food(name, user_id) VALUES ('Oats', 1)").execute(&dbpool).await.unwrap();
+
+    // The baseline is adopted instead of being run again (which would fail on the existing tables)
+    migrate_db(&dbpool).await.unwrap();
+    let migrations = sqlx::migrate!().migrations.len() as i64;
+    let applied : i64 = sqlx::query("SELECT COUNT(*) FROM _sqlx_migrations WHERE success").fetch_one(&dbpool).await.unwrap().get(0);
+    assert_eq!(applied, migrations);
+
+    // The data is kept, and upgraded by the later migrations
+    let food = sqlx::query("SELECT name, density FROM food").fetch_one(&dbpool).await.unwrap();
+    assert_eq!(food.get::<String, _>("name"), "Oats");
+    assert_eq!(food.get::<Option<f64>, _>("density"), None);
+    let recipe_table : bool = sqlx::query("SELECT to_regclass('recipe') IS NOT NULL").fetch_one(&dbpool).await.unwrap().get(0);
+    assert!(recipe_table);
+
+    // Nothing is left to apply the next time
+    migrate_db(&dbpool).await.unwrap();
+    let applied_again : i64 = sqlx::query("SELECT COUNT(*) FROM _sqlx_migrations").fetch_one(&dbpool).await.unwrap().get(0);
+    assert_eq!(applied_again, migrations);
+
+    dbpool.close().await;
+    main_dbpool.execute(format!("DROP DATABASE {} WITH (FORCE)", database_name).as_str()).await.unwrap();
+}
@@ -0,0 +1,50 @@
+use nutrinow::{
+    models::Nutrient,
+    routes::meals::{MealInfoFood, MealInfoNutrient},
+    utils::nutrition::{sum_food_nutrients, resolve_intake, nutrient_status, NutrientStatus}
+};
+
+fn nutrient(id : i32, name : &str, unit : &str) -> Nutrient {
+    Nutrient { id, name: name.to_string(), unit: unit.to_string() }
+}
+
+fn food(serving_base : f64, serving_amount : f64, base_nutrients : &[(&str, f64)]) -> MealInfoFood {
+    MealInfoFood {
+        id: 1,
+        name: "Food".to_string(),
+        meal_serving_id: 1,
+        serving_id: 1,
+        serving_base,
+        serving_amount,
+        serving_unit: "g".to_string(),
+        base_nutrients: base_nutrients
+            .iter()
+            .map(|(name, amount)| MealInfoNutrient { name: name.to_string(), amount: *amount, unit: "g".to_string() })
+            .collect()
+    }
+}
+
+#[test]
+fn test_sum_food_nutrients() {
+    let nutrients = vec![nutrient(1, "Protein", "g"), nutrient(2, "Carbohydrates", "g"), nutrient(3, "Fats", "g"), nutrient(4, "Fiber", "g")];
+    let foods = vec![
+        food(100.0, 200.0, &[("Protein", 10.0), ("Fats", 1.0)]),
+        food(1.0, 3.0, &[("Carbohydrates", 5.0)])
+    ];
+
+    let totals = sum_food_nutrients(&foods, &nutrients);
+    let amounts : Vec<(&str, f64)> = totals.iter().map(|n| (n.name.as_str(), n.amount)).collect();
+    assert_eq!(amounts, vec![("Calories", 158.0), ("Protein", 20.0), ("Carbohydrates", 15.0), ("Fats", 2.0), ("Fiber", 0.0)]);
+}
+
+#[test]
+fn test_nutrient_status() {
+    let min = resolve_intake(Some(1.5), true, 80.0);
+    let max = resolve_intake(Some(200.0), false, 80.0);
+    assert_eq!((min, max), (Some(120.0), Some(200.0)));
+
+    assert_eq!(nutrient_status(100.0, min, max), NutrientStatus::Below);
+    assert_eq!(nutrient_status(150.0, min, max), NutrientStatus::Within);
+    assert_eq!(nutrient_status(250.0, min, max), NutrientStatus::Above);
+    assert_eq!(nutrient_status(250.0, None, None), NutrientStatus::Within);
+}
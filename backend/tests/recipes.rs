@@ -0,0 +1,71 @@
+mod common;
+
+use nutrinow::utils::database::*;
+use sqlx::PgPool;
+
+/// Protein of the yield serving of a recipe
+async fn recipe_protein(recipe_id : i32, user_id : i32, dbpool : &PgPool) -> f64 {
+    let food = fetch_search_food(recipe_id, Some(user_id), dbpool).await.unwrap();
+    food.servings[0].nutrients.iter().find(|nutrient| nutrient.name == "Protein").unwrap().amount
+}
+
+#[actix_web::test]
+async fn refreshes_recipe_nutrients() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await;
+
+    // 1 cup of flour = 120 g
+    let flour_id = create_food(user_id, &"Flour".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let flour_base_id = fetch_search_food(flour_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    let cup_id = create_serving(flour_id, &"cup".to_string(), 120.0, Some(flour_base_id), &dbpool).await.unwrap();
+    set_serving_nutrient(flour_base_id, protein_id, 10.0, &dbpool).await.unwrap();
+
+    // An ingredient through a relative serving
+    let bread_id = create_recipe(user_id, &"Bread".to_string(), &"loaf".to_string(), 1.0, &dbpool).await.unwrap();
+    assert!(add_recipe_ingredient(bread_id, cup_id, 2.0, &dbpool).await.unwrap());
+    assert!((recipe_protein(bread_id, user_id, &dbpool).await - 24.0).abs() < 1e-9);
+
+    // A recipe inside a recipe
+    let bread_serving_id = fetch_recipe(bread_id, &dbpool).await.unwrap().serving_id;
+    let sandwich_id = create_recipe(user_id, &"Sandwich".to_string(), &"portion".to_string(), 1.0, &dbpool).await.unwrap();
+    assert!(add_recipe_ingredient(sandwich_id, bread_serving_id, 0.25, &dbpool).await.unwrap());
+    assert!((recipe_protein(sandwich_id, user_id, &dbpool).await - 6.0).abs() < 1e-9);
+
+    // Both are refreshed when an ingredient changes
+    set_serving_nutrient(flour_base_id, protein_id, 12.0, &dbpool).await.unwrap();
+    assert!((recipe_protein(bread_id, user_id, &dbpool).await - 28.8).abs() < 1e-9);
+    assert!((recipe_protein(sandwich_id, user_id, &dbpool).await - 7.2).abs() < 1e-9);
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn rejects_recipe_cycles() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+
+    let dough_id = create_recipe(user_id, &"Dough".to_string(), &"g".to_string(), 500.0, &dbpool).await.unwrap();
+    let dough_serving_id = fetch_recipe(dough_id, &dbpool).await.unwrap().serving_id;
+    let pizza_id = create_recipe(user_id, &"Pizza".to_string(), &"portion".to_string(), 4.0, &dbpool).await.unwrap();
+    let pizza_serving_id = fetch_recipe(pizza_id, &dbpool).await.unwrap().serving_id;
+    let menu_id = create_recipe(user_id, &"Menu".to_string(), &"portion".to_string(), 1.0, &dbpool).await.unwrap();
+    let menu_serving_id = fetch_recipe(menu_id, &dbpool).await.unwrap().serving_id;
+
+    assert!(add_recipe_ingredient(pizza_id, dough_serving_id, 250.0, &dbpool).await.unwrap());
+    assert!(add_recipe_ingredient(menu_id, pizza_serving_id, 1.0, &dbpool).await.unwrap());
+
+    // Directly, and through one or two other recipes
+    assert!(!add_recipe_ingredient(dough_id, dough_serving_id, 1.0, &dbpool).await.unwrap());
+    assert!(!add_recipe_ingredient(dough_id, pizza_serving_id, 1.0, &dbpool).await.unwrap());
+    assert!(!add_recipe_ingredient(dough_id, menu_serving_id, 1.0, &dbpool).await.unwrap());
+    assert!(fetch_recipe_info(dough_id, Some(user_id), &dbpool).await.unwrap().ingredients.is_empty());
+
+    // Same when an ingredient is changed to another serving
+    let ingredient_id = fetch_recipe_info(menu_id, Some(user_id), &dbpool).await.unwrap().ingredients[0].id;
+    let ingredient = fetch_recipe_ingredient(ingredient_id, &dbpool).await.unwrap();
+    assert!(!update_recipe_ingredient(&ingredient, menu_serving_id, 1.0, &dbpool).await.unwrap());
+    assert!(update_recipe_ingredient(&ingredient, dough_serving_id, 100.0, &dbpool).await.unwrap());
+
+    common::delete_user(user_id, &dbpool).await;
+}
@@ -0,0 +1,17 @@
+use nutrinow::utils::validation::{parse_id_list, check_order};
+
+#[test]
+fn parses_id_lists() {
+    assert_eq!(parse_id_list("3,1,2"), Some(vec![3, 1, 2]));
+    assert_eq!(parse_id_list(" 3, 1 ,2,"), Some(vec![3, 1, 2]));
+    assert_eq!(parse_id_list(""), Some(vec![]));
+    assert_eq!(parse_id_list("3,x"), None);
+}
+
+#[test]
+fn checks_orders() {
+    assert!(check_order(&[3, 1, 2], &[1, 2, 3]));
+    assert!(!check_order(&[3, 1], &[1, 2, 3])); // missing ID
+    assert!(!check_order(&[3, 1, 1], &[1, 2, 3])); // repeated ID
+    assert!(!check_order(&[3, 1, 2, 4], &[1, 2, 3])); // unknown ID
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use chrono::{Duration, Utc};
+use nutrinow::{create_app, settings::{Settings, SessionSettings}, utils::database::*};
+use uuid::Uuid;
+
+#[actix_web::test]
+async fn expires_sessions() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let settings = SessionSettings { duration_hours: 24, max_duration_hours: 72, ..Default::default() };
+
+    // Each use renews a session for 'duration_hours'
+    let session_id = create_session(user_id, None, &settings, &dbpool).await.unwrap();
+    let (session_user_id, expiry_date) = get_session_user_id(&session_id, &settings, &dbpool).await.unwrap();
+    assert_eq!(session_user_id, user_id);
+    assert!((expiry_date - (Utc::now() + Duration::hours(24))).num_seconds().abs() < 60);
+
+    // ... but never past 'max_duration_hours' after its creation
+    sqlx::query("UPDATE user_session SET created_at = NOW() - INTERVAL '60 hours' WHERE id = $1").bind(session_id).execute(&dbpool).await.unwrap();
+    let (_, expiry_date) = get_session_user_id(&session_id, &settings, &dbpool).await.unwrap();
+    assert!((expiry_date - (Utc::now() + Duration::hours(12))).num_seconds().abs() < 60);
+
+    // Expired sessions neither log in nor show up in the list
+    sqlx::query("UPDATE user_session SET expiry_date = NOW() - INTERVAL '1 second' WHERE id = $1").bind(session_id).execute(&dbpool).await.unwrap();
+    assert!(get_session_user_id(&session_id, &settings, &dbpool).await.is_none());
+    assert!(fetch_user_sessions(user_id, &dbpool).await.unwrap().is_empty());
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn lists_and_revokes_sessions() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let other_session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+
+    let req = test::TestRequest::get().uri("/api/sessions").cookie(Cookie::new("session_id", session_id.clone()));
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    let sessions = body["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|session| session["current"] == true).count(), 1);
+
+    // Sessions are revoked by their public ID, never by the secret one of the cookie
+    let other_public_id = sessions.iter().find(|session| session["current"] == false).unwrap()["id"].as_str().unwrap().to_string();
+    assert_ne!(other_public_id, other_session_id);
+    let req = common::post_form("/api/revoke_session", &session_id, &[("session_id", &other_session_id)]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::NOT_FOUND);
+    let req = common::post_form("/api/revoke_session", &session_id, &[("session_id", &other_public_id)]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get().uri("/api/sessions").cookie(Cookie::new("session_id", other_session_id));
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::UNAUTHORIZED);
+
+    // Logging out everywhere ends the current session too
+    let third_session_id = common::login(user_id, &dbpool).await;
+    let req = common::post_form("/api/logout_all", &session_id, &[]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    assert!(fetch_user_sessions(user_id, &dbpool).await.unwrap().is_empty());
+    for session_id in [session_id, third_session_id] {
+        assert!(get_session_user_id(&Uuid::parse_str(&session_id).unwrap(), &SessionSettings::default(), &dbpool).await.is_none());
+    }
+
+    common::delete_user(user_id, &dbpool).await;
+}
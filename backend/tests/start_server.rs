@@ -1,8 +1,7 @@
 use actix_web::test;
 use anyhow::Result;
-use env_logger;
 use log::info;
-use nutrinow::{connect_db, create_app, settings::Settings};
+use nutrinow::{connect_db, create_app, migrate_db, settings::Settings};
 
 #[actix_web::test]
 async fn test_start_server() -> Result<()> {
@@ -12,8 +11,10 @@ async fn test_start_server() -> Result<()> {
     info!("Settings: {:?}", settings);
     info!("Starting database");
     let dbpool = connect_db(&settings).await?;
+    info!("Applying migrations");
+    migrate_db(&dbpool).await?;
     info!("Starting web server");
-    let app = test::init_service(create_app(dbpool.clone())).await;
+    let app = test::init_service(create_app(dbpool.clone(), settings)).await;
     info!("Sending GET request to /");
     let req = test::TestRequest::get().uri("/").to_request();
     let resp = test::call_service(&app, req).await;
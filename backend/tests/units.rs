@@ -0,0 +1,71 @@
+use nutrinow::utils::units::*;
+
+fn assert_close(value : f64, expected : f64) {
+    assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+}
+
+#[test]
+fn parses_units() {
+    assert_eq!(parse_unit("g").map(|unit| unit.dimension), Some(Dimension::Mass));
+    assert_eq!(parse_unit("Tbsp").map(|unit| unit.dimension), Some(Dimension::Volume));
+    assert_eq!(parse_unit("cup (chopped)").map(|unit| unit.dimension), Some(Dimension::Volume));
+    assert_eq!(parse_unit("cups, diced").map(|unit| unit.dimension), Some(Dimension::Volume));
+    assert_eq!(parse_unit("μg"), parse_unit("mcg"));
+    assert_eq!(parse_unit("slice"), None);
+}
+
+#[test]
+fn converts_units_of_the_same_dimension() {
+    assert_close(convert_amount(1.5, "kg", "g", None).unwrap(), 1500.0);
+    assert_close(convert_amount(1.0, "lb", "oz", None).unwrap(), 16.0);
+    assert_close(convert_amount(1.0, "cup", "tbsp", None).unwrap(), 16.0);
+    assert_close(convert_amount(1.0, "tbsp", "tsp", None).unwrap(), 3.0);
+    assert_close(convert_amount(250.0, "ml", "l", None).unwrap(), 0.25);
+    assert_close(convert_amount(2.0, "slice", "slice", None).unwrap(), 2.0);
+}
+
+#[test]
+fn converts_between_mass_and_volume_with_density() {
+    assert_close(convert_amount(1.0, "cup", "g", Some(1.03)).unwrap(), 243.6858836);
+    assert_close(convert_amount(103.0, "g", "ml", Some(1.03)).unwrap(), 100.0);
+
+    assert_eq!(convert_amount(1.0, "cup", "g", None), Err(UnitError::MissingDensity("cup".to_string(), "g".to_string())));
+}
+
+#[test]
+fn rejects_incompatible_units() {
+    assert_eq!(convert_amount(1.0, "furlong", "g", None), Err(UnitError::Unknown("furlong".to_string())));
+    assert_eq!(convert_amount(1.0, "g", "slice", Some(1.0)), Err(UnitError::Mismatch("g".to_string(), "slice".to_string())));
+}
+
+#[test]
+fn converts_nutrient_amounts() {
+    assert_close(convert_nutrient_amount(0.16, "g", "mg").unwrap(), 160.0);
+    assert_close(convert_nutrient_amount(25.0, "mcg", "mg").unwrap(), 0.025);
+    assert_close(convert_nutrient_amount(100.0, "ml", "ml").unwrap(), 100.0);
+
+    assert!(convert_nutrient_amount(1.0, "ml", "g").is_err());
+    assert!(convert_nutrient_amount(1.0, "kcal", "mg").is_err());
+}
+
+#[test]
+fn converts_serving_amounts() {
+    let grams = ServingUnit { unit: "g".to_string(), base: None, density: None };
+    assert_close(convert_serving_amount(2.0, "oz", &grams).unwrap(), 56.69904625);
+    assert!(convert_serving_amount(1.0, "cup", &grams).is_err());
+
+    // 1 slice = 28g of the base serving
+    let slice = ServingUnit { unit: "slice".to_string(), base: Some(("g".to_string(), 28.0)), density: None };
+    assert_close(convert_serving_amount(3.0, "slice", &slice).unwrap(), 3.0);
+    assert_close(convert_serving_amount(56.0, "g", &slice).unwrap(), 2.0);
+
+    let milk = ServingUnit { unit: "g".to_string(), base: None, density: Some(1.03) };
+    assert_close(convert_serving_amount(100.0, "ml", &milk).unwrap(), 103.0);
+}
+
+#[test]
+fn calculates_density_from_portions() {
+    assert_close(density_from_portion("cup", 236.5882365).unwrap(), 1.0);
+    assert_eq!(density_from_portion("large", 50.0), None);
+    assert_eq!(density_from_portion("oz", 28.0), None);
+}
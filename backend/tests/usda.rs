@@ -0,0 +1,94 @@
+mod common;
+
+use std::collections::HashMap;
+use nutrinow::utils::{
+    database::*,
+    usda::{read_fdc_foods, convert_fdc_food, CatalogFood}
+};
+use uuid::Uuid;
+
+const FDC_JSON : &str = r#"{"FoundationFoods": [
+    {"fdcId": 1, "description": "Egg, whole, raw", "foodNutrients": [
+        {"type": "FoodNutrient", "nutrient": {"name": "Protein", "unitName": "g"}, "amount": 12.5},
+        {"type": "FoodNutrient", "nutrient": {"name": "Fatty acids, total monounsaturated", "unitName": "g"}, "amount": 3.5},
+        {"type": "FoodNutrient", "nutrient": {"name": "Fatty acids, total polyunsaturated", "unitName": "g"}, "amount": 2.0},
+        {"type": "FoodNutrient", "nutrient": {"name": "Energy", "unitName": "kcal"}, "amount": 143.0},
+        {"type": "FoodNutrient", "nutrient": {"name": "Water", "unitName": "g"}},
+        {"type": "FoodAttribute", "nutrient": {"name": "Iron, Fe", "unitName": "mg"}, "amount": 1.0}
+    ]}
+]}"#;
+
+#[test]
+fn converts_fdc_foods() {
+    let fdc_foods = read_fdc_foods(FDC_JSON.as_bytes()).unwrap();
+    assert_eq!(fdc_foods.len(), 1);
+
+    let food = convert_fdc_food(&fdc_foods[0]);
+    assert_eq!(food.fdc_id, 1);
+    assert_eq!(food.name, "Egg, whole, raw");
+    // Unmapped nutrients, missing amounts and non-nutrient entries are skipped,
+    // and nutrients mapped from several USDA nutrients are summed
+    assert_eq!(food.nutrients, vec![("Protein".to_string(), 12.5), ("Unsaturated Fat".to_string(), 5.5)]);
+}
+
+#[test]
+fn converts_fdc_portions() {
+    let json = r#"{"SRLegacyFoods": [
+        {"fdcId": 2, "description": "Bread, white", "foodPortions": [
+            {"amount": 2.0, "gramWeight": 56.0, "measureUnit": {"abbreviation": "undetermined"}, "modifier": "slice"},
+            {"amount": 0.5, "gramWeight": 120.0, "measureUnit": {"abbreviation": "cup"}, "modifier": "cubes"},
+            {"amount": 1.0, "gramWeight": 30.0, "measureUnit": {"abbreviation": "undetermined"}, "portionDescription": "1 piece", "modifier": "10205"},
+            {"amount": 1.0, "gramWeight": 100.0, "measureUnit": {"abbreviation": "undetermined"}, "portionDescription": "Quantity not specified"},
+            {"amount": 1.0, "measureUnit": {"abbreviation": "oz"}}
+        ]}
+    ]}"#;
+
+    let fdc_foods = read_fdc_foods(json.as_bytes()).unwrap();
+    let food = convert_fdc_food(&fdc_foods[0]);
+
+    assert_eq!(food.portions, vec![
+        ("slice".to_string(), 28.0),
+        ("cup (cubes)".to_string(), 240.0),
+        ("piece".to_string(), 30.0)
+    ]);
+
+    // the density comes from the first portion with a unit of volume
+    assert!((food.density.unwrap() - 240.0 / 236.5882365).abs() < 1e-9);
+}
+
+#[actix_web::test]
+async fn matches_catalog_foods_without_fdc_id() {
+    let dbpool = common::connect().await;
+    let protein_id = common::nutrient_id("Protein", &dbpool).await;
+    let nutrient_ids = HashMap::from([("Protein".to_string(), protein_id)]);
+    let name = format!("Bread {}", Uuid::new_v4());
+    let fdc_id = (Uuid::new_v4().as_u128() % 1_000_000_000) as i32 + 1_000_000_000;
+
+    // Added by hand before the importer, e.g with 'base.sql'
+    let food_id = create_food(CATALOG_USER_ID, &name, &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let base_id = fetch_search_food(food_id, None, &dbpool).await.unwrap().servings[0].id;
+
+    let food = CatalogFood {
+        fdc_id,
+        name: name.clone(),
+        nutrients: vec![("Protein".to_string(), 9.0)],
+        portions: vec![("slice".to_string(), 28.0)],
+        density: None,
+        barcodes: vec![]
+    };
+    let summary = import_catalog_foods(&[food], &nutrient_ids, &dbpool).await.unwrap();
+    assert_eq!((summary.foods_inserted, summary.foods_updated, summary.foods_matched), (0, 1, 1));
+
+    // The food keeps its base serving, which gets the imported nutrients
+    let food = fetch_search_food(food_id, None, &dbpool).await.unwrap();
+    assert_eq!(food.servings.len(), 2);
+    assert_eq!((food.servings[0].id, food.servings[0].nutrients[0].amount), (base_id, 9.0));
+    assert_eq!((food.servings[1].unit.as_str(), food.servings[1].relative), ("slice", Some(base_id)));
+
+    // Once matched, it is updated by its FDC ID
+    let food = CatalogFood { fdc_id, name: name.clone(), nutrients: vec![], portions: vec![], density: None, barcodes: vec![] };
+    let summary = import_catalog_foods(&[food], &nutrient_ids, &dbpool).await.unwrap();
+    assert_eq!((summary.foods_inserted, summary.foods_updated, summary.foods_matched), (0, 1, 0));
+
+    delete_food(food_id, &dbpool).await.unwrap();
+}
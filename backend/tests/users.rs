@@ -0,0 +1,107 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, ResponseError};
+use nutrinow::{create_app, models::ApiError, routes::login::LoginForm, settings::Settings, utils::database::*};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[actix_web::test]
+async fn keeps_catalog_user() {
+    let dbpool = common::connect().await;
+
+    // The catalog foods belong to this account, so it can't be deleted
+    assert!(delete_user_account(CATALOG_USER_ID, &dbpool).await.is_err());
+    assert!(fetch_user_account(CATALOG_USER_ID, &dbpool).await.is_some());
+    assert_eq!(ApiError::DeleteCatalogUser.status_code(), StatusCode::FORBIDDEN);
+
+    let user_id = common::create_user(&dbpool).await;
+    delete_user_account(user_id, &dbpool).await.unwrap();
+    assert!(fetch_user_account(user_id, &dbpool).await.is_none());
+}
+
+#[actix_web::test]
+async fn exports_recipes_favorites_and_serving_usage() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+
+    let food_id = create_food(user_id, &"Oats".to_string(), &"g".to_string(), 100.0, None, &dbpool).await.unwrap();
+    let serving_id = fetch_search_food(food_id, Some(user_id), &dbpool).await.unwrap().servings[0].id;
+    let recipe_id = create_recipe(user_id, &"Porridge".to_string(), &"portion".to_string(), 2.0, &dbpool).await.unwrap();
+    assert!(add_recipe_ingredient(recipe_id, serving_id, 80.0, &dbpool).await.unwrap());
+    add_favorite_food(user_id, food_id, &dbpool).await.unwrap();
+    record_serving_usage(user_id, serving_id, &dbpool).await.unwrap();
+    record_serving_usage(user_id, serving_id, &dbpool).await.unwrap();
+
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+    let req = test::TestRequest::get().uri("/api/export_user").cookie(actix_web::cookie::Cookie::new("session_id", session_id)).to_request();
+    let export : serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(export["recipes"].as_array().unwrap().len(), 1);
+    assert_eq!(export["recipes"][0]["food_id"], recipe_id);
+    assert_eq!(export["recipes"][0]["yield_amount"], 2.0);
+    assert_eq!(export["recipes"][0]["yield_unit"], "portion");
+    assert_eq!(export["recipes"][0]["ingredients"][0]["serving_id"], serving_id);
+    assert_eq!(export["recipes"][0]["ingredients"][0]["amount"], 80.0);
+    assert_eq!(export["favorites"][0]["food_id"], food_id);
+    assert_eq!(export["serving_usage"][0]["serving_id"], serving_id);
+    assert_eq!(export["serving_usage"][0]["use_count"], 2);
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn edits_profile() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = test::init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+    let email = format!("{}@test.localhost", Uuid::new_v4());
+
+    let req = common::post_form("/api/edit_user", &session_id, &[("name", "Renamed"), ("birthdate", "1985-06-15"), ("email", &email), ("gender", "F"), ("weight", "64.5")]);
+    let body : serde_json::Value = test::call_and_read_body_json(&app, req.to_request()).await;
+    assert_eq!((body["name"].as_str(), body["email"].as_str(), body["birthdate"].as_str()), (Some("Renamed"), Some(email.as_str()), Some("1985-06-15")));
+    assert_eq!((body["gender"].as_str(), body["weight"].as_f64()), (Some("F"), Some(64.5)));
+
+    // The new weight is logged in the weight history
+    let weight_log = fetch_user_weight_log(user_id, &dbpool).await.unwrap();
+    assert_eq!(weight_log.last().unwrap().weight, 64.5);
+
+    // Invalid profiles are rejected as a whole
+    let req = common::post_form("/api/edit_user", &session_id, &[("name", "Renamed"), ("birthdate", "2999-01-01"), ("email", "not an email"), ("gender", "F"), ("weight", "64.5")]);
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body : serde_json::Value = test::read_body_json(resp).await;
+    let fields : Vec<&str> = body["fields"].as_array().unwrap().iter().map(|field| field["field"].as_str().unwrap()).collect();
+    assert_eq!(fields, ["birthdate", "email"]);
+    assert_eq!(fetch_user_account(user_id, &dbpool).await.unwrap().email, email);
+
+    common::delete_user(user_id, &dbpool).await;
+}
+
+#[actix_web::test]
+async fn changes_password() {
+    let dbpool = common::connect().await;
+    let settings = Settings::load().unwrap();
+    let user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let other_session_id = common::login(user_id, &dbpool).await;
+    let email : String = sqlx::query("SELECT email FROM user_account WHERE id = $1").bind(user_id).fetch_one(&dbpool).await.unwrap().get("email");
+    let login = |password : &str| LoginForm { email: email.clone(), password: password.to_string() };
+    let app = test::init_service(create_app(dbpool.clone(), settings.clone())).await;
+
+    let req = common::post_form("/api/change_password", &session_id, &[("current_password", "wrong_password"), ("new_password", "new_password")]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::UNAUTHORIZED);
+
+    let req = common::post_form("/api/change_password", &session_id, &[("current_password", "test_password"), ("new_password", "new_password")]);
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+    assert!(authenticate_user(&login("test_password"), None, &settings, &dbpool).await.is_err());
+    assert!(authenticate_user(&login("new_password"), None, &settings, &dbpool).await.is_ok());
+
+    // Every other session is logged out
+    let (session_id, other_session_id) = (Uuid::parse_str(&session_id).unwrap(), Uuid::parse_str(&other_session_id).unwrap());
+    assert!(get_session_user_id(&session_id, &settings.session, &dbpool).await.is_some());
+    assert!(get_session_user_id(&other_session_id, &settings.session, &dbpool).await.is_none());
+
+    common::delete_user(user_id, &dbpool).await;
+}
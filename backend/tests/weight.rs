@@ -0,0 +1,51 @@
+mod common;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test::{call_service, init_service, TestRequest}};
+use chrono::NaiveDate;
+use nutrinow::{
+    create_app,
+    models::WeightEntry,
+    settings::Settings,
+    utils::weight::{moving_averages, weekly_rate}
+};
+
+fn entry(day : u32, weight : f64) -> WeightEntry {
+    WeightEntry { id: day as i32, user_id: 1, entry_date: NaiveDate::from_ymd_opt(2023, 1, day).unwrap(), weight }
+}
+
+#[test]
+fn moving_average_uses_window() {
+    let entries = [entry(1, 80.0), entry(2, 82.0), entry(8, 84.0), entry(9, 86.0)];
+    let averages = moving_averages(&entries, 7);
+
+    assert_eq!(averages, vec![80.0, 81.0, 83.0, 85.0]);
+}
+
+#[test]
+fn weekly_rate_from_linear_trend() {
+    let entries = [entry(1, 80.0), entry(8, 79.5), entry(15, 79.0)];
+    let rate = weekly_rate(&entries).unwrap();
+
+    assert!((rate + 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn weekly_rate_needs_two_dates() {
+    assert_eq!(weekly_rate(&[]), None);
+    assert_eq!(weekly_rate(&[entry(1, 80.0)]), None);
+}
+
+#[actix_web::test]
+async fn rejects_trend_before_the_earliest_date() {
+    let dbpool = common::connect().await;
+    let user_id = common::create_user(&dbpool).await;
+    let session_id = common::login(user_id, &dbpool).await;
+    let app = init_service(create_app(dbpool.clone(), Settings::load().unwrap())).await;
+
+    // The moving average window would start before the earliest date there is
+    let uri = format!("/api/weight_trend/{}/{}", NaiveDate::MIN, NaiveDate::MIN);
+    let req = TestRequest::get().uri(&uri).cookie(Cookie::new("session_id", session_id)).to_request();
+    assert_eq!(common::status_code(call_service(&app, req).await).await, (StatusCode::UNPROCESSABLE_ENTITY, "invalid_input".to_string()));
+
+    common::delete_user(user_id, &dbpool).await;
+}
@@ -29,6 +29,13 @@ function api_request(method, path, data = null, successCallback = null, errCallb
     .catch (function (err) {
         if (errCallback)
             errCallback();
+
+        // API errors come with an error status (e.g 401 or 422) and a message
+        if (err.response && err.response.data && err.response.data.err) {
+            errorStore.msgs.push(err.response.data.err);
+            return;
+        }
+
         errorStore.msgs.push(requestErrorMsg(path));
     });
 }